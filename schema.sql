@@ -1,7 +1,7 @@
 BEGIN TRANSACTION;
 CREATE TABLE IF NOT EXISTS energy_balance (
   user_id INTEGER PRIMARY KEY,
-  engergy INTEGER NOT NULL DEFAULT 0
+  energy INTEGER NOT NULL DEFAULT 0
 );
 CREATE TABLE IF NOT EXISTS custom_roles (
   role_id INTEGER PRIMARY KEY,
@@ -9,6 +9,66 @@ CREATE TABLE IF NOT EXISTS custom_roles (
   expires_at TIMESTAMP,
   auto_renewal BOOLEAN DEFAULT TRUE
 );
+CREATE TABLE IF NOT EXISTS inventory (
+  user_id INTEGER NOT NULL,
+  item TEXT NOT NULL,
+  quantity INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (user_id, item)
+);
+CREATE TABLE IF NOT EXISTS energy_boosts (
+  user_id INTEGER PRIMARY KEY,
+  multiplier INTEGER NOT NULL,
+  expires_at TIMESTAMP NOT NULL
+);
+CREATE TABLE IF NOT EXISTS loot_table (
+  loot_id INTEGER PRIMARY KEY,
+  weight INTEGER NOT NULL,
+  rarity TEXT NOT NULL,
+  reward TEXT NOT NULL,
+  amount INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS blind_box_pity (
+  user_id INTEGER PRIMARY KEY,
+  counter INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS blind_box_rolls (
+  roll_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  loot_id INTEGER NOT NULL,
+  rarity TEXT NOT NULL,
+  pity BOOLEAN NOT NULL DEFAULT FALSE,
+  rolled_at TIMESTAMP NOT NULL
+);
+CREATE TABLE IF NOT EXISTS booster_grace (
+  user_id INTEGER PRIMARY KEY,
+  ends_at TIMESTAMP NOT NULL
+);
+CREATE TABLE IF NOT EXISTS custom_role_reminders (
+  role_id INTEGER NOT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  reminder_offset INTEGER NOT NULL,
+  PRIMARY KEY (role_id, expires_at, reminder_offset)
+);
+CREATE TABLE IF NOT EXISTS energy_ledger (
+  entry_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  delta INTEGER NOT NULL,
+  reason TEXT NOT NULL,
+  reference INTEGER,
+  created_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS energy_ledger_user ON energy_ledger (user_id, entry_id);
+-- Open the ledger of balances that predate it
+INSERT INTO energy_ledger (user_id, delta, reason, created_at)
+SELECT user_id, energy, 'opening', unixepoch() FROM energy_balance
+WHERE energy != 0 AND user_id NOT IN (SELECT user_id FROM energy_ledger);
+-- `zone_id` is 0 for the zones following the global cooldown
+CREATE TABLE IF NOT EXISTS checkin_notes (
+  user_id INTEGER NOT NULL,
+  zone_id INTEGER NOT NULL DEFAULT 0,
+  last_checkin TIMESTAMP NOT NULL,
+  PRIMARY KEY (user_id, zone_id)
+);
 CREATE TABLE IF NOT EXISTS app_config (
   key TEXT PRIMARY KEY,
   value TEXT NOT NULL
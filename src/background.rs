@@ -1,29 +1,50 @@
-use std::fmt::Write;
+use std::{fmt::Write, time::Duration};
 
 use anyhow::{Result, anyhow};
-use tokio::join;
-use tracing::error;
+use tokio::{join, sync::watch, task::JoinSet};
+use tracing::{error, warn};
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
 
 use crate::{
     consts::MONTH_IN_SEC,
-    core::{app_state::AppState, database::CustomRole},
+    core::{
+        app_state::AppState,
+        database::{CustomRole, LedgerReason},
+    },
 };
 
-pub fn run(state: AppState) {
-    tokio::spawn(sync_energy(state.clone()));
-    tokio::spawn(scan_custom_roles(state.clone()));
+/// Spawns the background loops, which stop once `shutdown` changes.
+pub fn run(state: AppState, shutdown: watch::Receiver<bool>) -> JoinSet<()> {
+    let mut tasks = JoinSet::new();
+    tasks.spawn(sync_energy(state.clone(), shutdown.clone()));
+    tasks.spawn(scan_custom_roles(state.clone(), shutdown));
+    tasks
 }
 
-async fn sync_energy(state: AppState) {
-    loop {
-        tokio::time::sleep(state.config.env.sync_period).await;
+/// Sleeps for `period`, returns `false` if a shutdown was requested in the meantime.
+async fn idle(period: Duration, shutdown: &mut watch::Receiver<bool>) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(period) => true,
+        _ = shutdown.changed() => false,
+    }
+}
+
+async fn sync_energy(state: AppState, mut shutdown: watch::Receiver<bool>) {
+    // The final sync happens on shutdown, once every event handler is done
+    while idle(state.config.env.sync_period, &mut shutdown).await {
         if let Err(err) = state.cache.energy_balance.sync_energy_data().await {
             error!("Unable to sync energy: {err}")
         }
+        if let Err(err) = state.checkin_note.sync().await {
+            error!("Unable to sync check-ins: {err}")
+        }
     }
 }
 
-async fn scan_custom_roles(state: AppState) {
+async fn scan_custom_roles(state: AppState, mut shutdown: watch::Receiver<bool>) {
     async fn renew_role(
         state: AppState,
         mut role: CustomRole,
@@ -33,7 +54,12 @@ async fn scan_custom_roles(state: AppState) {
         let done = state
             .cache
             .energy_balance
-            .consume_energy(role.user_id, renew_fee)
+            .consume_energy(
+                role.user_id,
+                renew_fee,
+                LedgerReason::Renewal,
+                Some(role.role_id.get()),
+            )
             .await?;
 
         if !done {
@@ -73,16 +99,128 @@ async fn scan_custom_roles(state: AppState) {
         Ok(())
     }
 
+    async fn remind(
+        state: &AppState,
+        role: &CustomRole,
+        offsets: &[u64],
+        fee: u64,
+        now: u64,
+    ) -> Result<()> {
+        let expires_at = role.expires_at.unwrap_or_default();
+
+        // Only one message even if several reminders are due, e.g. after a downtime
+        let mut due = false;
+        for &offset in offsets.iter().filter(|&&offset| expires_at <= now + offset) {
+            due |= state
+                .db
+                .record_custom_role_reminder(role.role_id, expires_at, offset)
+                .await?;
+        }
+        if !due {
+            return Ok(());
+        }
+
+        let balance = state.cache.energy_balance.get(role.user_id).await?;
+        let renewal = match (role.auto_renewal, balance >= fee) {
+            (true, true) => format!(
+                "Auto-renewal is on and your balance of {balance} ⚡ covers the {fee} ⚡ fee, \
+                so it will renew automatically."
+            ),
+            (true, false) => format!(
+                "Auto-renewal is on, but your balance of {balance} ⚡ doesn't cover the \
+                {fee} ⚡ fee. Earn {} ⚡ more or the role will be removed.",
+                fee - balance
+            ),
+            (false, _) => format!(
+                "Auto-renewal is off, so the role will be removed. \
+                Turn it on from your inventory to keep it for {fee} ⚡."
+            ),
+        };
+        let content = format!(
+            "⏰ Your custom role **{name}** expires {expires_at}.\n{renewal}",
+            name = role.name,
+            expires_at = Timestamp::new(expires_at, Some(TimestampStyle::RelativeTime)).mention(),
+        );
+
+        if let Err(err) = state.direct_message(role.user_id, &content).await {
+            warn!(?err, "Unable to remind <{}> about their role", role.user_id);
+        }
+        Ok(())
+    }
+
+    async fn send_reminders(state: &AppState, now: u64) -> Result<()> {
+        let (offsets, fee) = {
+            let config = state.config.read().unwrap();
+            (
+                config.reminder_offsets.clone(),
+                config.service_fee.custom_role,
+            )
+        };
+
+        // Boosters keep their role for free, graces ending after expiry do the same
+        let roles = {
+            let user_custom_roles = state.cache.user_custom_roles.lock().unwrap();
+            let boosters = state.cache.boosters.lock().unwrap();
+            let booster_graces = state.cache.booster_graces.lock().unwrap();
+
+            user_custom_roles
+                .values()
+                .filter(|crole| {
+                    crole.expires_at.is_some_and(|expires_at| {
+                        expires_at > now
+                            && !boosters.contains(&crole.user_id)
+                            && booster_graces
+                                .get(&crole.user_id)
+                                .is_none_or(|&ends_at| ends_at < expires_at)
+                    })
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        // One failing role must not hold back the reminders of the others
+        for role in roles {
+            if let Err(err) = remind(state, &role, &offsets, fee, now).await {
+                error!(
+                    "Unable to remind <{}> about their role: {err}",
+                    role.user_id
+                );
+            }
+        }
+
+        state.db.delete_stale_custom_role_reminders(now).await?;
+        Ok(())
+    }
+
     loop {
         let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        // Former boosters whose grace period is over fall back to the normal renewal logic
+        let ended_graces = state
+            .cache
+            .booster_graces
+            .lock()
+            .unwrap()
+            .extract_if(|_, ends_at| *ends_at <= now)
+            .map(|(user_id, _)| user_id)
+            .collect::<Vec<_>>();
+
+        for user_id in ended_graces {
+            if let Err(err) = state.db.delete_booster_grace(user_id).await {
+                error!("Unable to end grace period of <{user_id}>: {err}");
+            }
+        }
+
         let expired_roles = {
             let mut user_custom_roles = state.cache.user_custom_roles.lock().unwrap();
             let boosters = state.cache.boosters.lock().unwrap();
+            let booster_graces = state.cache.booster_graces.lock().unwrap();
 
             user_custom_roles
                 .extract_if(|_, crole| {
                     crole.expires_at.is_some_and(|lifetime| lifetime < now)
                         && !boosters.contains(&crole.user_id)
+                        && !booster_graces.contains_key(&crole.user_id)
                 })
                 .map(|(_key, value)| value)
                 .collect::<Vec<_>>()
@@ -104,6 +242,12 @@ async fn scan_custom_roles(state: AppState) {
             }
         }
 
-        tokio::time::sleep(state.config.env.role_scan_period).await;
+        if let Err(err) = send_reminders(&state, now).await {
+            error!("Unable to send custom role reminders: {err}");
+        }
+
+        if !idle(state.config.env.role_scan_period, &mut shutdown).await {
+            return;
+        }
     }
 }
@@ -0,0 +1,446 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use tracing::info;
+use twilight_model::{
+    application::command::{Command, CommandOption, CommandType},
+    guild::Permissions,
+    id::{Id, marker::ApplicationMarker},
+};
+use twilight_util::builder::command::{
+    CommandBuilder, IntegerBuilder, StringBuilder, SubCommandBuilder, UserBuilder,
+};
+
+use crate::{
+    consts::interact,
+    core::{app_state::AppState, config::EDITABLE_KEYS},
+};
+
+/// Every command the bot answers to, as it should be registered in the guild.
+pub fn registry() -> Vec<Command> {
+    let menu = CommandBuilder::new(
+        interact::PIKABOO,
+        "Open the Pikaboo menu",
+        CommandType::ChatInput,
+    )
+    .description_localizations([("vi", "Mở menu Pikaboo")])
+    .build();
+
+    let quick = CommandBuilder::new(
+        interact::PIKABOO_QUICK,
+        "Quick access to Pikaboo features",
+        CommandType::ChatInput,
+    )
+    .description_localizations([("vi", "Truy cập nhanh các tính năng của Pikaboo")])
+    .option(
+        SubCommandBuilder::new(interact::INVENTORY, "Show your inventory")
+            .description_localizations([("vi", "Xem kho đồ của bạn")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::SHOP, "Open the shop")
+            .description_localizations([("vi", "Mở cửa hàng")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::CUSTOMROLE, "Manage your custom role")
+            .description_localizations([("vi", "Quản lý role tùy chỉnh của bạn")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::LEADERBOARD, "Show the energy leaderboard")
+            .description_localizations([("vi", "Xem bảng xếp hạng năng lượng")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::GIVE, "Give energy to another member")
+            .description_localizations([("vi", "Tặng năng lượng cho thành viên khác")])
+            .option(
+                UserBuilder::new(interact::USER, "Member receiving the energy")
+                    .description_localizations([("vi", "Thành viên nhận năng lượng")])
+                    .required(true),
+            )
+            .option(
+                IntegerBuilder::new(interact::AMOUNT, "Amount of energy to give")
+                    .description_localizations([("vi", "Lượng năng lượng muốn tặng")])
+                    .min_value(1)
+                    .required(true),
+            ),
+    )
+    .build();
+
+    let moderation = CommandBuilder::new(
+        interact::PIKABOO_MOD,
+        "Moderator tools",
+        CommandType::ChatInput,
+    )
+    .description_localizations([("vi", "Công cụ dành cho quản trị viên")])
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(
+        SubCommandBuilder::new(
+            interact::RECONCILE,
+            "Reconcile custom roles with the server",
+        )
+        .description_localizations([("vi", "Đối chiếu role tùy chỉnh với máy chủ")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::LEDGER, "Show a member's recent energy history")
+            .description_localizations([("vi", "Xem lịch sử năng lượng gần đây của thành viên")])
+            .option(
+                UserBuilder::new(interact::USER, "Member to inspect")
+                    .description_localizations([("vi", "Thành viên cần xem")])
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::FILTERS, "Show check-in filter statistics")
+            .description_localizations([("vi", "Xem thống kê bộ lọc điểm danh")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::CONFIG_VIEW, "Show the editable settings")
+            .description_localizations([("vi", "Xem các cài đặt có thể chỉnh sửa")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::CONFIG_SET, "Change a setting")
+            .description_localizations([("vi", "Thay đổi một cài đặt")])
+            .option(
+                StringBuilder::new(interact::KEY, "Setting to change")
+                    .description_localizations([("vi", "Cài đặt cần thay đổi")])
+                    .choices(EDITABLE_KEYS.map(|key| (key, key)))
+                    .required(true),
+            )
+            .option(
+                StringBuilder::new(interact::VALUE, "New value")
+                    .description_localizations([("vi", "Giá trị mới")])
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::GRANT, "Give energy to a member")
+            .description_localizations([("vi", "Cộng năng lượng cho thành viên")])
+            .option(
+                UserBuilder::new(interact::USER, "Member receiving the energy")
+                    .description_localizations([("vi", "Thành viên nhận năng lượng")])
+                    .required(true),
+            )
+            .option(
+                IntegerBuilder::new(interact::AMOUNT, "Amount of energy to grant")
+                    .description_localizations([("vi", "Lượng năng lượng cộng thêm")])
+                    .min_value(1)
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::REVOKE, "Take energy from a member")
+            .description_localizations([("vi", "Trừ năng lượng của thành viên")])
+            .option(
+                UserBuilder::new(interact::USER, "Member losing the energy")
+                    .description_localizations([("vi", "Thành viên bị trừ năng lượng")])
+                    .required(true),
+            )
+            .option(
+                IntegerBuilder::new(interact::AMOUNT, "Amount of energy to revoke")
+                    .description_localizations([("vi", "Lượng năng lượng bị trừ")])
+                    .min_value(1)
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::ROLE_EXPIRE, "Remove a member's custom role now")
+            .description_localizations([("vi", "Xóa role tùy chỉnh của thành viên ngay")])
+            .option(
+                UserBuilder::new(interact::USER, "Owner of the custom role")
+                    .description_localizations([("vi", "Chủ sở hữu role tùy chỉnh")])
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::ROLE_EXTEND, "Extend a member's custom role")
+            .description_localizations([("vi", "Gia hạn role tùy chỉnh của thành viên")])
+            .option(
+                UserBuilder::new(interact::USER, "Owner of the custom role")
+                    .description_localizations([("vi", "Chủ sở hữu role tùy chỉnh")])
+                    .required(true),
+            )
+            .option(
+                IntegerBuilder::new(interact::DAYS, "Days to add")
+                    .description_localizations([("vi", "Số ngày gia hạn")])
+                    .min_value(1)
+                    .required(true),
+            ),
+    )
+    .option(
+        SubCommandBuilder::new(interact::ZONES, "Manage the check-in zones")
+            .description_localizations([("vi", "Quản lý các khu vực điểm danh")]),
+    )
+    .option(
+        SubCommandBuilder::new(interact::INSPECT, "Show a member's economy state")
+            .description_localizations([("vi", "Xem tình trạng kinh tế của thành viên")])
+            .option(
+                UserBuilder::new(interact::USER, "Member to inspect")
+                    .description_localizations([("vi", "Thành viên cần xem")])
+                    .required(true),
+            ),
+    )
+    .build();
+
+    let gift = CommandBuilder::new(interact::GIFT_ENERGY, "", CommandType::User)
+        .name_localizations([("vi", "Tặng năng lượng")])
+        .build();
+
+    vec![menu, quick, moderation, gift]
+}
+
+#[derive(Debug)]
+pub enum Change {
+    Create(Command),
+    /// Discord overwrites a command created under an existing name
+    Update(Command),
+    Delete(Command),
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Create(command) => write!(f, "+ {} ({:?})", command.name, command.kind),
+            Change::Update(command) => write!(f, "~ {} ({:?})", command.name, command.kind),
+            Change::Delete(command) => write!(f, "- {} ({:?})", command.name, command.kind),
+        }
+    }
+}
+
+/// Lists what has to change for `existing` to match `registry`.
+pub fn diff(registry: &[Command], existing: &[Command]) -> Vec<Change> {
+    let same_command = |a: &Command, b: &Command| a.name == b.name && a.kind == b.kind;
+    let mut changes = vec![];
+
+    for command in registry {
+        match existing.iter().find(|other| same_command(command, other)) {
+            None => changes.push(Change::Create(command.clone())),
+            Some(other) if fingerprint(command) != fingerprint(other) => {
+                changes.push(Change::Update(command.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    for command in existing {
+        if !registry.iter().any(|other| same_command(command, other)) {
+            changes.push(Change::Delete(command.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Brings the guild commands in line with the registry, or only logs the diff in dry-run mode.
+pub async fn sync(state: &AppState, application_id: Id<ApplicationMarker>) -> Result<()> {
+    let guild_id = state.config.env.guild_id;
+    let client = state.app.interaction(application_id);
+
+    let existing = client.guild_commands(guild_id).await?.models().await?;
+    let changes = diff(&registry(), &existing);
+
+    if changes.is_empty() {
+        info!("Commands are up to date");
+        return Ok(());
+    }
+
+    let dry_run = state.config.env.commands_dry_run;
+    for change in changes {
+        info!(dry_run, "Command change: {change}");
+        if dry_run {
+            continue;
+        }
+
+        match change {
+            Change::Create(command) | Change::Update(command) => {
+                create(state, application_id, &command).await?;
+            }
+            Change::Delete(command) => {
+                let Some(command_id) = command.id else {
+                    continue;
+                };
+                client.delete_guild_command(guild_id, command_id).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn create(
+    state: &AppState,
+    application_id: Id<ApplicationMarker>,
+    command: &Command,
+) -> Result<()> {
+    let guild_id = state.config.env.guild_id;
+    let request = state
+        .app
+        .interaction(application_id)
+        .create_guild_command(guild_id);
+
+    match command.kind {
+        CommandType::User => {
+            let mut request = request.user(&command.name);
+            if let Some(permissions) = command.default_member_permissions {
+                request = request.default_member_permissions(permissions);
+            }
+            if let Some(localizations) = &command.name_localizations {
+                request = request.name_localizations(localizations);
+            }
+            request.await?;
+        }
+        _ => {
+            let mut request = request
+                .chat_input(&command.name, &command.description)
+                .command_options(&command.options);
+            if let Some(permissions) = command.default_member_permissions {
+                request = request.default_member_permissions(permissions);
+            }
+            if let Some(localizations) = &command.description_localizations {
+                request = request.description_localizations(localizations);
+            }
+            if let Some(localizations) = &command.name_localizations {
+                request = request.name_localizations(localizations);
+            }
+            request.await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps only what the registry describes, with Discord's defaults made explicit.
+fn fingerprint(command: &Command) -> Command {
+    let mut command = command.clone();
+    command.id = None;
+    command.application_id = None;
+    command.guild_id = None;
+    command.version = Id::new(1);
+    command.contexts = None;
+    command.integration_types = None;
+    command.dm_permission = None;
+    command.nsfw = command.nsfw.filter(|&nsfw| nsfw);
+    command.description_localizations = command
+        .description_localizations
+        .filter(|localizations| !localizations.is_empty());
+    command.name_localizations = command
+        .name_localizations
+        .filter(|localizations| !localizations.is_empty());
+    command.options = normalize_options(command.options);
+    command
+}
+
+fn normalize_options(options: Vec<CommandOption>) -> Vec<CommandOption> {
+    options
+        .into_iter()
+        .map(|mut option| {
+            option.required = option.required.filter(|&required| required);
+            option.autocomplete = option.autocomplete.filter(|&autocomplete| autocomplete);
+            option.description_localizations = option
+                .description_localizations
+                .filter(|localizations| !localizations.is_empty());
+            option.name_localizations = option
+                .name_localizations
+                .filter(|localizations| !localizations.is_empty());
+            option.options = option
+                .options
+                .map(normalize_options)
+                .filter(|options| !options.is_empty());
+            option
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// The registry as Discord sends it back, with ids and defaults filled in.
+    fn fetched() -> Vec<Command> {
+        registry()
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut command)| {
+                command.id = Some(Id::new(i as u64 + 1));
+                command.application_id = Some(Id::new(100));
+                command.guild_id = Some(Id::new(200));
+                command.version = Id::new(300);
+                command.nsfw = Some(false);
+                command.description_localizations =
+                    Some(command.description_localizations.unwrap_or_default());
+                command.name_localizations = Some(command.name_localizations.unwrap_or_default());
+                command.options = fill_options(command.options);
+                command
+            })
+            .collect()
+    }
+
+    fn fill_options(options: Vec<CommandOption>) -> Vec<CommandOption> {
+        options
+            .into_iter()
+            .map(|mut option| {
+                option.required = Some(option.required.unwrap_or_default());
+                option.name_localizations = Some(option.name_localizations.unwrap_or_default());
+                option.options = option.options.map(fill_options);
+                option
+            })
+            .collect()
+    }
+
+    fn find<'a>(commands: &'a mut [Command], name: &str) -> &'a mut Command {
+        commands
+            .iter_mut()
+            .find(|command| command.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn unchanged_registry_has_no_changes() {
+        assert!(diff(&registry(), &registry()).is_empty());
+        assert!(diff(&registry(), &fetched()).is_empty());
+    }
+
+    #[test]
+    fn changed_options_are_updated() {
+        let mut existing = fetched();
+        find(&mut existing, interact::PIKABOO_QUICK).options.pop();
+
+        let changes = diff(&registry(), &existing);
+        assert!(matches!(
+            changes.as_slice(),
+            [Change::Update(command)] if command.name == interact::PIKABOO_QUICK
+        ));
+    }
+
+    #[test]
+    fn changed_localizations_are_updated() {
+        let mut existing = fetched();
+        find(&mut existing, interact::PIKABOO).description_localizations =
+            Some(HashMap::from([("vi".to_owned(), "Menu cũ".to_owned())]));
+        find(&mut existing, interact::GIFT_ENERGY).name_localizations = Some(HashMap::new());
+
+        let changes = diff(&registry(), &existing);
+        assert!(matches!(
+            changes.as_slice(),
+            [Change::Update(menu), Change::Update(gift)]
+                if menu.name == interact::PIKABOO && gift.name == interact::GIFT_ENERGY
+        ));
+    }
+
+    #[test]
+    fn removed_commands_are_deleted() {
+        let mut existing = fetched();
+        let mut removed =
+            CommandBuilder::new("old", "Removed command", CommandType::ChatInput).build();
+        removed.id = Some(Id::new(99));
+        existing.push(removed);
+        existing.retain(|command| command.name != interact::PIKABOO_MOD);
+
+        let changes = diff(&registry(), &existing);
+        assert!(matches!(
+            changes.as_slice(),
+            [Change::Create(created), Change::Delete(deleted)]
+                if created.name == interact::PIKABOO_MOD && deleted.name == "old"
+        ));
+    }
+}
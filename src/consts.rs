@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use twilight_model::id::{Id, marker::UserMarker};
+
 pub const HOUR_IN_SEC_STR: &str = "3600";
 pub const MINUTE_IN_SEC_STR: &str = "60";
 pub const MINUTE_IN_SEC: u64 = 60;
@@ -5,6 +9,18 @@ pub const HOUR_IN_SEC: u64 = MINUTE_IN_SEC * 60;
 pub const DAY_IN_SEC: u64 = HOUR_IN_SEC * 24;
 pub const MONTH_IN_SEC: u64 = DAY_IN_SEC * 30;
 
+/// How long the gateway may take to acknowledge the close frame on shutdown
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handlers still running after this long get a deferred response, Discord waits for 3 seconds
+pub const DEFER_THRESHOLD: Duration = Duration::from_millis(2000);
+
+/// Account collecting the transfer tax, no Discord snowflake is that small
+pub const COMMUNITY_POT_ID: Id<UserMarker> = Id::new(1);
+
+/// Nonce of the member request used to rebuild the booster set
+pub const MEMBER_CHUNK_NONCE: &str = "boosters";
+
 pub mod interact {
     pub const PIKABOO: &str = "pikaboo";
     pub const PIKABOO_QUICK: &str = "pikaboo-quick";
@@ -13,9 +29,31 @@ pub mod interact {
     pub const INVENTORY: &str = "inventory";
     pub const SHOP: &str = "shop";
     pub const ZONES: &str = "zones";
+    pub const ZONES_ADD: &str = "zones-add";
+    pub const ZONES_REMOVE: &str = "zones-remove";
     pub const GAMES: &str = "games";
     pub const FAQS: &str = "faqs";
     pub const ABOUT: &str = "about";
+    pub const RECONCILE: &str = "reconcile";
+    pub const LEDGER: &str = "ledger";
+    pub const FILTERS: &str = "filters";
+    pub const CONFIG_VIEW: &str = "config-view";
+    pub const CONFIG_SET: &str = "config-set";
+    pub const GRANT: &str = "grant";
+    pub const REVOKE: &str = "revoke";
+    pub const ROLE_EXPIRE: &str = "role-expire";
+    pub const ROLE_EXTEND: &str = "role-extend";
+    pub const INSPECT: &str = "inspect";
+    pub const KEY: &str = "key";
+    pub const VALUE: &str = "value";
+    pub const DAYS: &str = "days";
+    pub const USER: &str = "user";
+    pub const AMOUNT: &str = "amount";
+    pub const GIVE: &str = "give";
+    pub const LEADERBOARD: &str = "leaderboard";
+    pub const LEADERBOARD_PAGE: &str = "leaderboard-page";
+    pub const GIFT_ENERGY: &str = "Gift energy";
+    pub const GIFT_ENERGY_MODAL: &str = "gift-energy";
 
     pub const CUSTOMROLE: &str = "customrole";
     pub const NICKNAME: &str = "nickname";
@@ -37,6 +75,14 @@ pub mod interact {
 
     pub const CUSTOMROLE_SUBSCRIBE: &str = "customrole-subscribe";
     pub const CUSTOMROLE_UNSUBSCRIBE: &str = "customrole-unsubscribe";
+
+    pub const CUSTOMROLE_EDIT_NAME: &str = "customrole-edit-name";
+    pub const CUSTOMROLE_EDIT_COLOR: &str = "customrole-edit-color";
+    pub const CUSTOMROLE_EDIT_ICON: &str = "customrole-edit-icon";
+    pub const CUSTOMROLE_TOGGLE_MENTIONABLE: &str = "customrole-toggle-mentionable";
+    pub const ROLE_NAME: &str = "role-name";
+    pub const ROLE_COLOR: &str = "role-color";
+    pub const ROLE_ICON: &str = "role-icon";
 }
 
 pub mod colors {
@@ -1,21 +1,27 @@
 use std::{
-    collections::HashSet,
+    collections::HashMap,
     ops::Deref,
     sync::{Arc, Mutex, RwLock},
     time::Duration,
 };
 
-use tracing::info;
+use anyhow::Result;
+use lru::LruCache;
+use rand::{SeedableRng, rngs::StdRng};
+use tracing::{error, info};
+use twilight_gateway::MessageSender;
 use twilight_http::Client as HttpClient;
+use twilight_mention::Mention;
 use twilight_model::id::{
     Id,
-    marker::{GuildMarker, UserMarker},
+    marker::{ChannelMarker, GuildMarker, UserMarker},
 };
 
 use crate::core::{
     cache::Cache,
     config::{Config, ConfigInner},
-    database::DatabaseClient,
+    database::{ConnectionWrapper, DatabaseClient},
+    message_filter::MessageFilter,
 };
 
 #[derive(Debug, Clone)]
@@ -27,22 +33,32 @@ pub struct EnvConfig {
     pub discord_token: String,
     pub libsql_url: String,
     pub libsql_auth_token: String,
+    /// Fixed seed for the blind box RNG, drawn from the OS when unset
+    pub blind_box_seed: Option<u64>,
+    /// Only log the command registration diff instead of applying it
+    pub commands_dry_run: bool,
+    /// Run the check-in rules reading the message content, which needs the privileged
+    /// message content intent
+    pub content_filters: bool,
 }
 
 #[derive(Debug)]
 pub struct AppStateInner {
     pub app: HttpClient,
+    pub gateway: MessageSender,
     pub config: Config,
     pub db: DatabaseClient,
     pub checkin_note: CheckinNote,
+    pub message_filter: MessageFilter,
     pub cache: Cache,
+    pub rng: Mutex<StdRng>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AppState(Arc<AppStateInner>);
 
 impl AppState {
-    pub async fn new(env: EnvConfig) -> AppState {
+    pub async fn new(env: EnvConfig, gateway: MessageSender) -> AppState {
         info!("Initializing AppState contents...");
 
         let app = HttpClient::new(env.discord_token.clone());
@@ -63,16 +79,62 @@ impl AppState {
             .expect("Failed fetching Config");
         info!("Config initialized.");
 
-        let checkin_note = CheckinNote::new(config.inner.clone());
+        let rng = match config.env.blind_box_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        let checkin_note = CheckinNote::new(config.inner.clone(), db.clone_conn())
+            .await
+            .expect("Failed to load check-in notes");
         info!("Check-in note initialized.");
+
+        let message_filter = MessageFilter::new(config.inner.clone(), config.env.content_filters);
         AppState(Arc::new(AppStateInner {
             app,
+            gateway,
             config,
             db,
             cache,
             checkin_note,
+            message_filter,
+            rng: Mutex::new(rng),
         }))
     }
+
+    /// Sends `content` to the user in a direct message.
+    pub async fn direct_message(&self, user_id: Id<UserMarker>, content: &str) -> Result<()> {
+        let channel = self
+            .app
+            .create_private_channel(user_id)
+            .await?
+            .model()
+            .await?;
+        self.app.create_message(channel.id).content(content).await?;
+        Ok(())
+    }
+
+    /// Sends `content` to the admin log channel, or only logs it when none is configured.
+    pub async fn admin_log(&self, content: &str) -> Result<()> {
+        let channel_id = self.config.read().unwrap().admin_log_channel;
+        let Some(channel_id) = channel_id else {
+            info!("{content}");
+            return Ok(());
+        };
+
+        self.app.create_message(channel_id).content(content).await?;
+        Ok(())
+    }
+
+    /// Records an action taken by a moderator, in the logs and in the admin log channel.
+    pub async fn moderation_log(&self, moderator_id: Id<UserMarker>, action: &str) {
+        info!(%moderator_id, "Moderation: {action}");
+
+        let content = format!("🛡️ {} {action}", moderator_id.mention());
+        if let Err(err) = self.admin_log(&content).await {
+            error!(?err, "Unable to log the moderation action");
+        }
+    }
 }
 
 impl Deref for AppState {
@@ -83,34 +145,259 @@ impl Deref for AppState {
     }
 }
 
+/// Notes kept at most, the oldest one is dropped to make room for a new one
+const MAX_CHECKIN_NOTES: usize = 100_000;
+
+/// A user in a cooldown zone, `None` standing for the zones following the global cooldown
+pub type CheckinKey = (Id<UserMarker>, Option<Id<ChannelMarker>>);
+
 #[derive(Debug)]
 pub struct CheckinNote {
     config: Arc<RwLock<ConfigInner>>,
+    connection: ConnectionWrapper,
     inner: Mutex<CheckinNoteInner>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct CheckinNoteInner {
-    last_timestamp: u64,
-    notes: HashSet<Id<UserMarker>>,
+    /// Timestamp of the last counted check-in of each user, per cooldown zone,
+    /// from the oldest to the most recent
+    last_checkins: LruCache<CheckinKey, u64>,
+    /// Check-ins noted since the last sync
+    unsynced: HashMap<CheckinKey, u64>,
+}
+
+impl CheckinNoteInner {
+    fn new() -> CheckinNoteInner {
+        CheckinNoteInner {
+            last_checkins: LruCache::new(MAX_CHECKIN_NOTES.try_into().unwrap()),
+            unsynced: HashMap::new(),
+        }
+    }
+
+    /// Forgets the oldest check-ins as long as their cooldown is over.
+    fn prune(&mut self, now: u64, longest_cooldown: u64) {
+        while self
+            .last_checkins
+            .peek_lru()
+            .is_some_and(|(_, &last_checkin)| last_checkin + longest_cooldown <= now)
+        {
+            self.last_checkins.pop_lru();
+        }
+    }
 }
 
 impl CheckinNote {
-    pub fn new(config: Arc<RwLock<ConfigInner>>) -> CheckinNote {
-        CheckinNote {
-            config,
-            inner: Default::default(),
+    pub async fn new(
+        config: Arc<RwLock<ConfigInner>>,
+        connection: ConnectionWrapper,
+    ) -> Result<CheckinNote> {
+        let (persist, longest_cooldown) = {
+            let config = config.read().unwrap();
+            (config.persist_checkins, config.longest_cooldown())
+        };
+
+        let mut inner = CheckinNoteInner::new();
+        if persist {
+            let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+            let since = now.saturating_sub(longest_cooldown);
+            let mut checkins = connection.fetch_checkins(since).await?;
+            checkins.sort_unstable_by_key(|&(_, last_checkin)| last_checkin);
+            for (key, last_checkin) in checkins {
+                inner.last_checkins.put(key, last_checkin);
+            }
         }
+
+        Ok(CheckinNote {
+            config,
+            connection,
+            inner: Mutex::new(inner),
+        })
     }
 
-    pub fn checkin(&self, user_id: Id<UserMarker>, timestamp: u64) -> bool {
-        let cd = self.config.read().unwrap().cooldown;
+    /// Notes a check-in, returns `false` if the user is still on cooldown in this zone.
+    ///
+    /// The cooldown slides: it starts from the last check-in that was counted.
+    pub fn checkin(
+        &self,
+        user_id: Id<UserMarker>,
+        channel_id: Id<ChannelMarker>,
+        timestamp: u64,
+    ) -> bool {
+        let (cd, key, persist, longest_cooldown) = {
+            let config = self.config.read().unwrap();
+            let (cd, zone) = match config.zones.get(&channel_id).and_then(|zone| zone.cooldown) {
+                Some(cd) => (cd, Some(channel_id)),
+                None => (config.cooldown, None),
+            };
+            (
+                cd,
+                (user_id, zone),
+                config.persist_checkins,
+                config.longest_cooldown(),
+            )
+        };
         let mut inner = self.inner.lock().unwrap();
 
-        if inner.last_timestamp + cd < timestamp {
-            inner.notes.clear();
+        let on_cooldown = inner
+            .last_checkins
+            .peek(&key)
+            .is_some_and(|&last_checkin| timestamp < last_checkin + cd);
+        if on_cooldown {
+            return false;
+        }
+
+        // Noting moves the key to the most recent end, the oldest one goes if there is no room
+        inner.last_checkins.put(key, timestamp);
+        if persist {
+            inner.unsynced.insert(key, timestamp);
+        }
+        inner.prune(timestamp, longest_cooldown);
+
+        true
+    }
+
+    /// Saves the check-ins noted since the last sync, when persistence is on.
+    pub async fn sync(&self) -> Result<()> {
+        let (persist, longest_cooldown) = {
+            let config = self.config.read().unwrap();
+            (config.persist_checkins, config.longest_cooldown())
+        };
+        let unsynced = std::mem::take(&mut self.inner.lock().unwrap().unsynced);
+
+        if !persist {
+            return Ok(());
+        }
+
+        let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let expired_before = now.saturating_sub(longest_cooldown);
+        let result = self
+            .connection
+            .sync_checkins(&unsynced, expired_before)
+            .await;
+
+        if result.is_err() {
+            // Check-ins noted in the meantime are newer, they take precedence
+            let mut inner = self.inner.lock().unwrap();
+            for (key, last_checkin) in unsynced {
+                inner.unsynced.entry(key).or_insert(last_checkin);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::config::{ConfigWrapperBuilder, Zone};
+
+    use super::*;
+
+    const COOLDOWN: u64 = 60;
+    const ZONE_COOLDOWN: u64 = 300;
+
+    fn user(id: u64) -> Id<UserMarker> {
+        Id::new(id)
+    }
+
+    fn channel(id: u64) -> Id<ChannelMarker> {
+        Id::new(id)
+    }
+
+    /// Channel `2` overrides the global cooldown, channel `1` follows it.
+    async fn checkin_note() -> CheckinNote {
+        let zone = Zone {
+            cooldown: Some(ZONE_COOLDOWN),
+            ..Default::default()
+        };
+        let config = ConfigWrapperBuilder {
+            cooldown: Some(COOLDOWN),
+            custom_role_fee: Some(0),
+            nickname_fee: Some(0),
+            zones: Some(HashMap::from([
+                (channel(1), Zone::default()),
+                (channel(2), zone),
+            ])),
+            ..Default::default()
         }
+        .try_build()
+        .unwrap();
 
-        inner.notes.insert(user_id)
+        let connection = ConnectionWrapper::in_memory().await.unwrap();
+        CheckinNote::new(Arc::new(RwLock::new(config)), connection)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn cooldown_ends_exactly_after_the_last_checkin() {
+        let note = checkin_note().await;
+
+        assert!(note.checkin(user(1), channel(1), 1_000));
+        assert!(!note.checkin(user(1), channel(1), 1_000 + COOLDOWN - 1));
+        assert!(note.checkin(user(1), channel(1), 1_000 + COOLDOWN));
+    }
+
+    #[tokio::test]
+    async fn cooldown_slides_from_the_last_counted_checkin() {
+        let note = checkin_note().await;
+
+        assert!(note.checkin(user(1), channel(1), 1_000));
+        // Refused attempts don't push the cooldown back
+        assert!(!note.checkin(user(1), channel(1), 1_030));
+        assert!(note.checkin(user(1), channel(1), 1_060));
+        assert!(!note.checkin(user(1), channel(1), 1_100));
+        assert!(note.checkin(user(1), channel(1), 1_120));
+    }
+
+    #[tokio::test]
+    async fn cooldowns_are_per_user() {
+        let note = checkin_note().await;
+
+        assert!(note.checkin(user(1), channel(1), 1_000));
+        assert!(note.checkin(user(2), channel(1), 1_000));
+        assert!(!note.checkin(user(2), channel(1), 1_001));
+    }
+
+    #[tokio::test]
+    async fn zone_cooldown_overrides_the_global_one() {
+        let note = checkin_note().await;
+
+        assert!(note.checkin(user(1), channel(2), 1_000));
+        assert!(!note.checkin(user(1), channel(2), 1_000 + COOLDOWN));
+        assert!(note.checkin(user(1), channel(2), 1_000 + ZONE_COOLDOWN));
+
+        // Zones following the global cooldown have their own, shared one
+        assert!(note.checkin(user(1), channel(1), 1_000));
+        assert!(!note.checkin(user(1), channel(3), 1_001));
+    }
+
+    #[tokio::test]
+    async fn expired_checkins_are_pruned() {
+        let note = checkin_note().await;
+
+        assert!(note.checkin(user(1), channel(1), 1_000));
+        assert!(note.checkin(user(2), channel(2), 1_100));
+        assert_eq!(note.inner.lock().unwrap().last_checkins.len(), 2);
+
+        // The longest cooldown is over for the first one only
+        assert!(note.checkin(user(3), channel(1), 1_000 + ZONE_COOLDOWN));
+        let inner = note.inner.lock().unwrap();
+        assert!(!inner.last_checkins.contains(&(user(1), None)));
+        assert!(inner.last_checkins.contains(&(user(2), Some(channel(2)))));
+        assert_eq!(inner.last_checkins.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn notes_are_bounded() {
+        let note = checkin_note().await;
+
+        for id in 1..=MAX_CHECKIN_NOTES as u64 + 1 {
+            assert!(note.checkin(user(id), channel(1), 1_000));
+        }
+        let inner = note.inner.lock().unwrap();
+        assert_eq!(inner.last_checkins.len(), MAX_CHECKIN_NOTES);
+        assert!(!inner.last_checkins.contains(&(user(1), None)));
     }
 }
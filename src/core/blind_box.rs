@@ -0,0 +1,276 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{Result, anyhow};
+use rand::Rng;
+
+use crate::consts::HOUR_IN_SEC;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    /// Whether this rarity satisfies the pity guarantee
+    pub fn is_rare(self) -> bool {
+        self >= Rarity::Rare
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rarity::Common => "common",
+            Rarity::Rare => "rare",
+            Rarity::Epic => "epic",
+            Rarity::Legendary => "legendary",
+        }
+    }
+}
+
+impl FromStr for Rarity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "common" => Rarity::Common,
+            "rare" => Rarity::Rare,
+            "epic" => Rarity::Epic,
+            "legendary" => Rarity::Legendary,
+            _ => return Err(anyhow!("Unknown rarity `{s}`")),
+        })
+    }
+}
+
+impl Display for Rarity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rarity::Common => write!(f, "Common"),
+            Rarity::Rare => write!(f, "Rare"),
+            Rarity::Epic => write!(f, "Epic"),
+            Rarity::Legendary => write!(f, "Legendary"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reward {
+    /// Energy given back to the balance
+    Energy(u64),
+    /// Nickname changes added to the inventory
+    Nickname(u64),
+    /// Energy boost lasting the given seconds
+    EnergyBoost(u64),
+    /// Seconds added to the owned custom role's lifetime
+    CustomRoleExtension(u64),
+}
+
+impl Reward {
+    pub fn new(kind: &str, amount: u64) -> Result<Reward> {
+        Ok(match kind {
+            "energy" => Reward::Energy(amount),
+            "nickname" => Reward::Nickname(amount),
+            "energy_boost" => Reward::EnergyBoost(amount),
+            "custom_role_extension" => Reward::CustomRoleExtension(amount),
+            _ => return Err(anyhow!("Unknown reward `{kind}`")),
+        })
+    }
+}
+
+impl Display for Reward {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Reward::Energy(amount) => write!(f, "{amount} ⚡"),
+            Reward::Nickname(amount) => write!(f, "{amount} nickname change(s) 📝"),
+            Reward::EnergyBoost(secs) => write!(f, "{}h energy boost", secs / HOUR_IN_SEC),
+            Reward::CustomRoleExtension(secs) => {
+                write!(f, "{}h custom role extension 🎀", secs / HOUR_IN_SEC)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LootEntry {
+    pub loot_id: u64,
+    pub weight: u64,
+    pub rarity: Rarity,
+    pub reward: Reward,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Roll {
+    pub entry: LootEntry,
+    /// Whether the pity guarantee forced this roll to be rare
+    pub pity: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LootTable {
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    pub fn new(entries: Vec<LootEntry>) -> LootTable {
+        LootTable { entries }
+    }
+
+    /// Picks an entry among those accepted by `eligible`, proportionally to its weight.
+    ///
+    /// With `pity` set, only rare entries can drop, unless none of them is eligible.
+    pub fn roll(
+        &self,
+        rng: &mut impl Rng,
+        pity: bool,
+        eligible: impl Fn(&LootEntry) -> bool,
+    ) -> Option<Roll> {
+        let pool = |rare_only: bool| {
+            self.entries
+                .iter()
+                .filter(|entry| entry.weight > 0 && eligible(entry))
+                .filter(|entry| !rare_only || entry.rarity.is_rare())
+                .collect::<Vec<_>>()
+        };
+
+        let (pool, pity) = match pity {
+            true => match pool(true) {
+                rare_pool if !rare_pool.is_empty() => (rare_pool, true),
+                _ => (pool(false), false),
+            },
+            false => (pool(false), false),
+        };
+
+        let total = pool.iter().map(|entry| entry.weight).sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut ticket = rng.random_range(0..total);
+        pool.into_iter()
+            .find(|entry| match ticket.checked_sub(entry.weight) {
+                Some(rest) => {
+                    ticket = rest;
+                    false
+                }
+                None => true,
+            })
+            .map(|&entry| Roll { entry, pity })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    const ROLLS: usize = 10_000;
+
+    fn entry(loot_id: u64, weight: u64, rarity: Rarity) -> LootEntry {
+        LootEntry {
+            loot_id,
+            weight,
+            rarity,
+            reward: Reward::Energy(loot_id),
+        }
+    }
+
+    fn table() -> LootTable {
+        LootTable::new(vec![
+            entry(1, 70, Rarity::Common),
+            entry(2, 20, Rarity::Rare),
+            entry(3, 10, Rarity::Epic),
+        ])
+    }
+
+    fn drops(table: &LootTable, seed: u64, pity: bool) -> Vec<Roll> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..ROLLS)
+            .map(|_| table.roll(&mut rng, pity, |_| true).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn drops_follow_the_weights() {
+        let table = table();
+        let rolls = drops(&table, 42, false);
+
+        for entry in &table.entries {
+            let count = rolls
+                .iter()
+                .filter(|roll| roll.entry.loot_id == entry.loot_id)
+                .count();
+            let expected = ROLLS * entry.weight as usize / 100;
+            assert!(
+                count.abs_diff(expected) < ROLLS / 50,
+                "loot {} dropped {count} times, expected about {expected}",
+                entry.loot_id
+            );
+        }
+        assert!(rolls.iter().all(|roll| !roll.pity));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_drops() {
+        let table = table();
+        let ids = |seed| {
+            drops(&table, seed, false)
+                .into_iter()
+                .map(|roll| roll.entry.loot_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(7), ids(7));
+    }
+
+    #[test]
+    fn pity_guarantees_a_rare_drop() {
+        let rolls = drops(&table(), 42, true);
+        assert!(
+            rolls
+                .iter()
+                .all(|roll| roll.entry.rarity.is_rare() && roll.pity)
+        );
+    }
+
+    #[test]
+    fn pity_falls_back_when_no_rare_drop_is_eligible() {
+        let table = table();
+        let mut rng = StdRng::seed_from_u64(42);
+        let roll = table
+            .roll(&mut rng, true, |entry| entry.rarity == Rarity::Common)
+            .unwrap();
+        assert_eq!(roll.entry.loot_id, 1);
+        assert!(!roll.pity);
+    }
+
+    #[test]
+    fn empty_table_drops_nothing() {
+        let mut rng = StdRng::seed_from_u64(42);
+        assert!(
+            LootTable::default()
+                .roll(&mut rng, false, |_| true)
+                .is_none()
+        );
+        assert!(
+            LootTable::default()
+                .roll(&mut rng, true, |_| true)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn zero_weight_entries_never_drop() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let table = LootTable::new(vec![entry(1, 0, Rarity::Legendary)]);
+        assert!(table.roll(&mut rng, true, |_| true).is_none());
+
+        let table = LootTable::new(vec![
+            entry(1, 0, Rarity::Legendary),
+            entry(2, 5, Rarity::Common),
+        ]);
+        assert!(
+            (0..ROLLS).all(|_| table.roll(&mut rng, false, |_| true).unwrap().entry.loot_id == 2)
+        );
+    }
+}
@@ -4,126 +4,423 @@ use std::{
     sync::Mutex,
 };
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use lru::LruCache;
-use twilight_model::id::{Id, marker::UserMarker};
+use twilight_model::id::{
+    Id,
+    marker::{MessageMarker, RoleMarker, UserMarker},
+};
 
-use crate::core::database::{ConnectionWrapper, CustomRole};
+use crate::{
+    consts::COMMUNITY_POT_ID,
+    core::database::{ConnectionWrapper, CustomRole, EnergyBoost, LedgerEntry, LedgerReason},
+};
 
 #[derive(Debug)]
 pub struct Cache {
     pub energy_balance: EnergyBalance,
     pub user_custom_roles: UserCustomRole,
+    pub energy_boosts: EnergyBoosts,
     pub boosters: Mutex<HashSet<Id<UserMarker>>>,
+    /// End of the grace period of each former booster
+    pub booster_graces: Mutex<HashMap<Id<UserMarker>, u64>>,
+    pub member_chunks: Mutex<MemberChunks>,
+    /// Timestamp of the last edit applied to each custom role
+    pub role_edits: Mutex<HashMap<Id<RoleMarker>, u64>>,
+    /// Messages that earned energy recently, in case they get deleted
+    pub checkin_messages: Mutex<HashMap<Id<MessageMarker>, CheckinMessage>>,
+    pub pending_orders: PendingOrders,
+    /// Gifts being processed, so the daily limit can't be exceeded by concurrent gifts
+    pub pending_gifts: PendingOrders,
 }
 
 impl Cache {
     pub async fn new(conn: ConnectionWrapper) -> Result<Cache> {
         Ok(Cache {
             energy_balance: EnergyBalance::new(50, conn.clone()),
-            user_custom_roles: UserCustomRole::new(conn).await?,
+            user_custom_roles: UserCustomRole::new(conn.clone()).await?,
+            energy_boosts: EnergyBoosts::new(conn.clone()).await?,
             boosters: Default::default(),
+            booster_graces: Mutex::new(conn.fetch_booster_graces().await?.into_iter().collect()),
+            member_chunks: Default::default(),
+            role_edits: Default::default(),
+            checkin_messages: Default::default(),
+            pending_orders: Default::default(),
+            pending_gifts: Default::default(),
         })
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct CheckinMessage {
+    pub user_id: Id<UserMarker>,
+    pub energy: u64,
+    pub earned_at: u64,
+}
+
+/// Users with an order being processed, so a submitted order can't be paid twice
+#[derive(Debug, Default)]
+pub struct PendingOrders(Mutex<HashSet<Id<UserMarker>>>);
+
+impl PendingOrders {
+    /// Marks an order of the user as in progress until the returned guard is dropped,
+    /// `None` if another one already is.
+    pub fn start(&self, user_id: Id<UserMarker>) -> Option<PendingOrder<'_>> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(user_id)
+            .then_some(PendingOrder {
+                orders: self,
+                user_id,
+            })
+    }
+}
+
+pub struct PendingOrder<'a> {
+    orders: &'a PendingOrders,
+    user_id: Id<UserMarker>,
+}
+
+impl Drop for PendingOrder<'_> {
+    fn drop(&mut self) {
+        self.orders.0.lock().unwrap().remove(&self.user_id);
+    }
+}
+
+/// Boosters collected from member chunks until the last chunk arrives
+#[derive(Debug, Default)]
+pub struct MemberChunks {
+    pub received: u32,
+    pub boosters: HashSet<Id<UserMarker>>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct EnergyData {
     pub user_id: Id<UserMarker>,
     pub energy: u64,
     pub is_dirty: bool,
+    /// Bumped on every change, tells whether a flushed snapshot is still current
+    pub version: u64,
 }
 
+/// Write-behind cache of energy balances.
+///
+/// Every balance change goes through the cache and reaches the database on the next
+/// [`EnergyBalance::sync_energy_data`], along with its ledger entries.
 #[derive(Debug)]
 pub struct EnergyBalance {
     connection: ConnectionWrapper,
-    balance: Mutex<LruCache<Id<UserMarker>, EnergyData>>,
+    inner: Mutex<EnergyBalanceInner>,
+    /// Only one flush at a time, so an older snapshot never overwrites a newer one
+    sync_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug)]
+struct EnergyBalanceInner {
+    balance: LruCache<Id<UserMarker>, EnergyData>,
+    /// Dirty entries pushed out of `balance`, or changed by a transfer without being loaded
+    /// into it, kept until they are persisted
+    evicted: HashMap<Id<UserMarker>, EnergyData>,
+    /// Ledger entries of cached changes, written along with the next sync
+    ledger: Vec<LedgerEntry>,
+    /// Number of successful syncs, used to detect reads racing with a flush
+    syncs: u64,
+}
+
+impl EnergyBalanceInner {
+    fn get_mut(&mut self, user_id: Id<UserMarker>) -> Option<&mut EnergyData> {
+        if !self.balance.contains(&user_id) {
+            let data = self.evicted.remove(&user_id)?;
+            self.insert(data);
+        }
+        self.balance.get_mut(&user_id)
+    }
+
+    /// Cached balance of the user, evicted ones included, without touching the LRU order.
+    fn peek(&self, user_id: Id<UserMarker>) -> Option<&EnergyData> {
+        self.balance
+            .peek(&user_id)
+            .or_else(|| self.evicted.get(&user_id))
+    }
+
+    /// Applies the entry to the cached balance of the user, wherever it is.
+    fn record(&mut self, entry: LedgerEntry) -> Result<()> {
+        let data = self
+            .balance
+            .peek_mut(&entry.user_id)
+            .or_else(|| self.evicted.get_mut(&entry.user_id))
+            .ok_or_else(|| anyhow!("Balance of <{}> isn't cached", entry.user_id))?;
+        data.energy = data.energy.saturating_add_signed(entry.delta);
+        data.is_dirty = true;
+        data.version += 1;
+        self.ledger.push(entry);
+        Ok(())
+    }
+
+    fn insert(&mut self, data: EnergyData) {
+        let user_id = data.user_id;
+        match self.balance.push(user_id, data) {
+            Some((evicted_id, evicted)) if evicted_id != user_id && evicted.is_dirty => {
+                self.evicted.insert(evicted_id, evicted);
+            }
+            _ => {}
+        }
+    }
 }
 
 impl EnergyBalance {
     fn new(cap: usize, connection: ConnectionWrapper) -> EnergyBalance {
         EnergyBalance {
-            balance: Mutex::new(LruCache::new(cap.try_into().unwrap())),
+            inner: Mutex::new(EnergyBalanceInner {
+                balance: LruCache::new(cap.try_into().unwrap()),
+                evicted: HashMap::new(),
+                ledger: vec![],
+                syncs: 0,
+            }),
+            sync_lock: tokio::sync::Mutex::new(()),
             connection,
         }
     }
 
-    async fn fetch(&self, user_id: Id<UserMarker>) -> Result<u64> {
-        let energy = self.connection.fetch_energy(user_id).await?;
-        let data = EnergyData {
-            user_id,
-            energy,
-            is_dirty: false,
-        };
+    /// Loads the balance of the user into the cache, unless it's already there.
+    async fn fetch(&self, user_id: Id<UserMarker>) -> Result<()> {
+        loop {
+            let syncs = {
+                let mut inner = self.inner.lock().unwrap();
+                if inner.get_mut(user_id).is_some() {
+                    return Ok(());
+                }
+                inner.syncs
+            };
 
-        let evicted_data = self.balance.lock().unwrap().push(user_id, data);
-        if let Some((_, data)) = evicted_data {
-            self.connection.sync_energy_one(data).await?;
+            let energy = self.connection.fetch_energy(user_id).await?;
+
+            let mut inner = self.inner.lock().unwrap();
+            // A flush in between may have persisted a newer balance than the one we read
+            if inner.syncs != syncs {
+                continue;
+            }
+            if inner.get_mut(user_id).is_none() {
+                inner.insert(EnergyData {
+                    user_id,
+                    energy,
+                    is_dirty: false,
+                    version: 0,
+                });
+            }
+            return Ok(());
         }
+    }
 
-        Ok(energy)
+    /// Applies `f` to the cached balance of the user, loading it first on a miss.
+    ///
+    /// A ledger entry returned by `f` marks the balance as changed.
+    async fn with_data<T>(
+        &self,
+        user_id: Id<UserMarker>,
+        f: impl FnOnce(&mut EnergyData) -> (T, Option<LedgerEntry>),
+    ) -> Result<T> {
+        loop {
+            {
+                let mut inner = self.inner.lock().unwrap();
+                if let Some(data) = inner.get_mut(user_id) {
+                    let (output, entry) = f(data);
+                    if let Some(entry) = entry {
+                        data.is_dirty = true;
+                        data.version += 1;
+                        inner.ledger.push(entry);
+                    }
+                    return Ok(output);
+                }
+            }
+
+            self.fetch(user_id).await?;
+        }
     }
 
     pub async fn get(&self, user_id: Id<UserMarker>) -> Result<u64> {
-        let data = self.balance.lock().unwrap().get(&user_id).cloned();
-        let energy = match data {
-            Some(data) => data.energy,
-            None => self.fetch(user_id).await?,
-        };
+        self.with_data(user_id, |data| (data.energy, None)).await
+    }
 
-        Ok(energy)
+    pub async fn add_energy(
+        &self,
+        user_id: Id<UserMarker>,
+        amount: u64,
+        reason: LedgerReason,
+        reference: Option<u64>,
+    ) -> Result<()> {
+        let entry = LedgerEntry::new(user_id, amount as i64, reason, reference);
+        self.with_data(user_id, |data| {
+            data.energy += amount;
+            ((), Some(entry))
+        })
+        .await
     }
 
-    pub async fn add_one(&self, user_id: Id<UserMarker>) -> Result<()> {
-        loop {
-            let cache_hit = self
-                .balance
-                .lock()
-                .unwrap()
-                .get_mut(&user_id)
-                .map(|data| data.energy += 1)
-                .is_some();
-
-            if cache_hit {
-                return Ok(());
+    pub async fn consume_energy(
+        &self,
+        user_id: Id<UserMarker>,
+        amount: u64,
+        reason: LedgerReason,
+        reference: Option<u64>,
+    ) -> Result<bool> {
+        let entry = LedgerEntry::new(user_id, -(amount as i64), reason, reference);
+        self.with_data(user_id, |data| {
+            if data.energy < amount {
+                return (false, None);
             }
-
-            self.fetch(user_id).await?;
-        }
+            data.energy -= amount;
+            (true, Some(entry))
+        })
+        .await
     }
 
-    pub async fn consume_energy(&self, user_id: Id<UserMarker>, amount: u64) -> Result<bool> {
-        let status = self.balance.lock().unwrap().get_mut(&user_id).map(|data| {
-            if data.energy > amount {
-                data.energy -= amount;
-                data.is_dirty = true;
-                return true;
+    /// Takes back up to `amount`, returns how much could be taken.
+    pub async fn take_energy(
+        &self,
+        user_id: Id<UserMarker>,
+        amount: u64,
+        reason: LedgerReason,
+        reference: Option<u64>,
+    ) -> Result<u64> {
+        self.with_data(user_id, |data| {
+            let taken = data.energy.min(amount);
+            if taken == 0 {
+                return (0, None);
             }
-            false
-        });
+            data.energy -= taken;
+            let entry = LedgerEntry::new(user_id, -(taken as i64), reason, reference);
+            (taken, Some(entry))
+        })
+        .await
+    }
 
-        if let Some(status) = status {
-            return Ok(status);
+    /// Moves `amount` from `from` to `to`, `tax` of which goes to the community pot instead.
+    ///
+    /// The three balances change together and reach the database in the same sync.
+    pub async fn transfer(
+        &self,
+        from: Id<UserMarker>,
+        to: Id<UserMarker>,
+        amount: u64,
+        tax: u64,
+    ) -> Result<bool> {
+        let reason = LedgerReason::Transfer;
+        let mut entries = vec![
+            LedgerEntry::new(from, -(amount as i64), reason, Some(to.get())),
+            LedgerEntry::new(to, (amount - tax) as i64, reason, Some(from.get())),
+        ];
+        if tax > 0 {
+            entries.push(LedgerEntry::new(
+                COMMUNITY_POT_ID,
+                tax as i64,
+                reason,
+                Some(from.get()),
+            ));
         }
 
-        let status = self.connection.consume_energy(user_id, amount).await?;
-        Ok(status)
+        // Balances read from the database, with the number of syncs before the read
+        let mut loaded = HashMap::new();
+        loop {
+            let (missing, syncs) = {
+                let mut inner = self.inner.lock().unwrap();
+                let syncs = inner.syncs;
+                // A flush in between may have persisted a newer balance than the one we read
+                loaded.retain(|_, (_, loaded_syncs)| *loaded_syncs == syncs);
+
+                let missing = entries
+                    .iter()
+                    .map(|entry| entry.user_id)
+                    .filter(|&user_id| {
+                        inner.peek(user_id).is_none() && !loaded.contains_key(&user_id)
+                    })
+                    .collect::<HashSet<_>>();
+
+                if missing.is_empty() {
+                    let balance = inner
+                        .peek(from)
+                        .map_or_else(|| loaded[&from].0, |data| data.energy);
+                    if balance < amount {
+                        return Ok(false);
+                    }
+
+                    // Joining the evicted balances, the accounts can't push each other out
+                    for (user_id, (energy, _)) in loaded {
+                        if inner.peek(user_id).is_none() {
+                            inner.evicted.insert(
+                                user_id,
+                                EnergyData {
+                                    user_id,
+                                    energy,
+                                    is_dirty: false,
+                                    version: 0,
+                                },
+                            );
+                        }
+                    }
+                    for entry in entries {
+                        inner.record(entry)?;
+                    }
+                    return Ok(true);
+                }
+                (missing, syncs)
+            };
+
+            for user_id in missing {
+                let energy = self.connection.fetch_energy(user_id).await?;
+                loaded.insert(user_id, (energy, syncs));
+            }
+        }
     }
 
+    /// Persists every dirty balance, evicted ones included, with the pending ledger entries.
     pub async fn sync_energy_data(&self) -> Result<()> {
-        let dirty_data = self
-            .balance
-            .lock()
-            .unwrap()
-            .iter_mut()
-            .filter(|(_, data)| data.is_dirty)
-            .map(|(_, data)| {
-                data.is_dirty = false;
-                *data
-            })
-            .collect::<Vec<_>>();
+        let _guard = self.sync_lock.lock().await;
+
+        let (dirty_data, ledger) = {
+            let mut inner = self.inner.lock().unwrap();
+            let dirty_data = inner
+                .balance
+                .iter()
+                .map(|(_, data)| data)
+                .chain(inner.evicted.values())
+                .filter(|data| data.is_dirty)
+                .copied()
+                .collect::<Vec<_>>();
+            (dirty_data, std::mem::take(&mut inner.ledger))
+        };
+
+        if dirty_data.is_empty() && ledger.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.connection.sync_energy_data(&dirty_data, &ledger).await;
+
+        let mut inner = self.inner.lock().unwrap();
+        if result.is_err() {
+            // Keep the entries for the next attempt, ahead of the newer ones
+            inner.ledger.splice(0..0, ledger);
+            return result;
+        }
 
-        self.connection.sync_energy_data(dirty_data).await
+        // Entries changed during the flush stay dirty for the next one
+        for synced in dirty_data {
+            if let Some(data) = inner.balance.peek_mut(&synced.user_id) {
+                if data.version == synced.version {
+                    data.is_dirty = false;
+                }
+            } else if inner
+                .evicted
+                .get(&synced.user_id)
+                .is_some_and(|data| data.version == synced.version)
+            {
+                inner.evicted.remove(&synced.user_id);
+            }
+        }
+        inner.syncs += 1;
+
+        result
     }
 }
 
@@ -157,3 +454,254 @@ impl Deref for UserCustomRole {
         &self.0
     }
 }
+
+#[derive(Debug, Default)]
+pub struct EnergyBoosts(Mutex<HashMap<Id<UserMarker>, EnergyBoost>>);
+
+impl EnergyBoosts {
+    pub async fn new(conn: ConnectionWrapper) -> Result<EnergyBoosts> {
+        let collection = conn.fetch_energy_boosts().await?;
+        let map = collection
+            .into_iter()
+            .map(|boost| (boost.user_id, boost))
+            .collect();
+        Ok(EnergyBoosts(Mutex::new(map)))
+    }
+    pub fn get(&self, user_id: Id<UserMarker>) -> Option<EnergyBoost> {
+        self.0.lock().unwrap().get(&user_id).copied()
+    }
+    pub fn update(&self, boost: EnergyBoost) {
+        self.0.lock().unwrap().insert(boost.user_id, boost);
+    }
+    /// Returns the multiplier to apply to `user_id`'s earnings at `now`.
+    pub fn multiplier(&self, user_id: Id<UserMarker>, now: u64) -> u64 {
+        self.get(user_id)
+            .filter(|boost| boost.is_active(now))
+            .map_or(1, |boost| boost.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::mpsc::{self, RecvTimeoutError},
+        thread,
+        time::Duration,
+    };
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    /// Below the three accounts of a transfer, so transfers always go through eviction
+    const CAPACITY: usize = 2;
+    /// The community pot is one of them, so transfers with tax move it around as well
+    const USERS: u64 = 6;
+    const STEPS: usize = 500;
+    const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    async fn energy_balance() -> EnergyBalance {
+        EnergyBalance::new(CAPACITY, ConnectionWrapper::in_memory().await.unwrap())
+    }
+
+    fn random_user(rng: &mut StdRng) -> Id<UserMarker> {
+        Id::new(rng.random_range(1..=USERS))
+    }
+
+    /// Checks that the database holds the expected balances, and a ledger adding up to them.
+    async fn assert_persisted(
+        energy_balance: &EnergyBalance,
+        expected: &HashMap<Id<UserMarker>, u64>,
+    ) {
+        let conn = &energy_balance.connection;
+        for user_id in (1..=USERS).map(Id::new) {
+            let expected = expected.get(&user_id).copied().unwrap_or_default();
+            assert_eq!(
+                conn.fetch_energy(user_id).await.unwrap(),
+                expected,
+                "persisted balance of {user_id}"
+            );
+
+            let ledger = conn.fetch_ledger(user_id, u64::MAX >> 1).await.unwrap();
+            let total = ledger.iter().map(|entry| entry.delta).sum::<i64>();
+            assert_eq!(total, expected as i64, "ledger of {user_id}");
+        }
+    }
+
+    async fn run_sequence(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let energy_balance = energy_balance().await;
+        let mut expected = HashMap::<Id<UserMarker>, u64>::new();
+
+        for _ in 0..STEPS {
+            let user_id = random_user(&mut rng);
+            let amount = rng.random_range(0..100);
+            let reason = LedgerReason::Checkin;
+
+            match rng.random_range(0..5) {
+                0 => {
+                    energy_balance
+                        .add_energy(user_id, amount, reason, None)
+                        .await
+                        .unwrap();
+                    *expected.entry(user_id).or_default() += amount;
+                }
+                1 => {
+                    let consumed = energy_balance
+                        .consume_energy(user_id, amount, reason, None)
+                        .await
+                        .unwrap();
+                    let balance = expected.entry(user_id).or_default();
+                    assert_eq!(consumed, *balance >= amount);
+                    if consumed {
+                        *balance -= amount;
+                    }
+                }
+                2 => {
+                    let to = random_user(&mut rng);
+                    let tax = rng.random_range(0..=amount);
+                    let moved = energy_balance
+                        .transfer(user_id, to, amount, tax)
+                        .await
+                        .unwrap();
+                    assert_eq!(
+                        moved,
+                        expected.get(&user_id).copied().unwrap_or_default() >= amount
+                    );
+                    if moved {
+                        *expected.entry(user_id).or_default() -= amount;
+                        *expected.entry(to).or_default() += amount - tax;
+                        *expected.entry(COMMUNITY_POT_ID).or_default() += tax;
+                    }
+                }
+                3 => {
+                    energy_balance.sync_energy_data().await.unwrap();
+                    assert_persisted(&energy_balance, &expected).await;
+                }
+                _ => {}
+            }
+
+            // Reading also loads the user, evicting someone else
+            let visible = energy_balance.get(user_id).await.unwrap();
+            assert_eq!(
+                visible,
+                expected.get(&user_id).copied().unwrap_or_default(),
+                "visible balance of {user_id}"
+            );
+            assert!(energy_balance.inner.lock().unwrap().balance.len() <= CAPACITY);
+        }
+
+        energy_balance.sync_energy_data().await.unwrap();
+        assert_persisted(&energy_balance, &expected).await;
+        for (&user_id, &energy) in &expected {
+            assert_eq!(energy_balance.get(user_id).await.unwrap(), energy);
+        }
+    }
+
+    /// Runs the sequence on its own thread, so a livelock fails the test instead of hanging it.
+    fn run_bounded(seed: u64) {
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(run_sequence(seed));
+            let _ = done_tx.send(());
+        });
+
+        match done_rx.recv_timeout(SEQUENCE_TIMEOUT) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => panic!("seed {seed} didn't finish in time"),
+            Err(RecvTimeoutError::Disconnected) => panic!("seed {seed} failed"),
+        }
+    }
+
+    #[test]
+    fn random_operations_keep_cache_and_database_in_step() {
+        for seed in 0..20 {
+            run_bounded(seed);
+        }
+    }
+
+    #[tokio::test]
+    async fn transfer_between_uncached_accounts() {
+        let energy_balance = energy_balance().await;
+        let (from, to) = (Id::new(10), Id::new(11));
+
+        energy_balance
+            .add_energy(from, 100, LedgerReason::Checkin, None)
+            .await
+            .unwrap();
+        energy_balance.sync_energy_data().await.unwrap();
+        // Fills the cache with other users, the three accounts can't all fit in it anyway
+        for user_id in (20..20 + CAPACITY as u64).map(Id::new) {
+            energy_balance.get(user_id).await.unwrap();
+        }
+
+        assert!(energy_balance.transfer(from, to, 60, 10).await.unwrap());
+        assert!(!energy_balance.transfer(from, to, 60, 10).await.unwrap());
+        assert!(energy_balance.inner.lock().unwrap().balance.len() <= CAPACITY);
+
+        energy_balance.sync_energy_data().await.unwrap();
+        let conn = &energy_balance.connection;
+        assert_eq!(conn.fetch_energy(from).await.unwrap(), 40);
+        assert_eq!(conn.fetch_energy(to).await.unwrap(), 50);
+        assert_eq!(conn.fetch_energy(COMMUNITY_POT_ID).await.unwrap(), 10);
+        assert!(energy_balance.inner.lock().unwrap().evicted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn eviction_keeps_unsynced_credit() {
+        let energy_balance = energy_balance().await;
+        let reason = LedgerReason::Checkin;
+        let first = Id::new(10);
+
+        energy_balance
+            .add_energy(first, 30, reason, None)
+            .await
+            .unwrap();
+        for user_id in (11..11 + CAPACITY as u64).map(Id::new) {
+            energy_balance
+                .add_energy(user_id, 5, reason, None)
+                .await
+                .unwrap();
+        }
+        {
+            let inner = energy_balance.inner.lock().unwrap();
+            assert!(!inner.balance.contains(&first));
+            assert!(inner.evicted.contains_key(&first));
+        }
+
+        // Still visible before the sync, and persisted after it
+        assert_eq!(energy_balance.get(first).await.unwrap(), 30);
+        energy_balance.sync_energy_data().await.unwrap();
+        assert_eq!(
+            energy_balance.connection.fetch_energy(first).await.unwrap(),
+            30
+        );
+        assert!(energy_balance.inner.lock().unwrap().evicted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn evicted_credit_is_persisted_without_reloading() {
+        let energy_balance = energy_balance().await;
+        let reason = LedgerReason::Checkin;
+        let first = Id::new(10);
+
+        energy_balance
+            .add_energy(first, 30, reason, None)
+            .await
+            .unwrap();
+        for user_id in (11..11 + CAPACITY as u64).map(Id::new) {
+            energy_balance.get(user_id).await.unwrap();
+        }
+        energy_balance.sync_energy_data().await.unwrap();
+
+        assert_eq!(
+            energy_balance.connection.fetch_energy(first).await.unwrap(),
+            30
+        );
+        assert!(energy_balance.inner.lock().unwrap().evicted.is_empty());
+    }
+}
@@ -1,39 +1,248 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ops::Deref,
     sync::{Arc, RwLock},
 };
 
 use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
 use twilight_model::id::{Id, marker::ChannelMarker};
 
-use crate::core::{app_state::EnvConfig, database::ConnectionWrapper};
+use crate::{
+    consts::{DAY_IN_SEC, MINUTE_IN_SEC},
+    core::{app_state::EnvConfig, database::ConnectionWrapper},
+};
+
+const DEFAULT_ENERGY_BOOST_FEE: u64 = 100;
+const DEFAULT_ENERGY_BOOST_MULTIPLIER: u64 = 2;
+const DEFAULT_ENERGY_BOOST_DURATION: u64 = DAY_IN_SEC;
+const DEFAULT_BLIND_BOX_FEE: u64 = 50;
+const DEFAULT_BLIND_BOX_PITY: u64 = 10;
+const DEFAULT_ROLE_EDIT_COOLDOWN: u64 = MINUTE_IN_SEC * 5;
+const DEFAULT_BOOSTER_GRACE_PERIOD: u64 = DAY_IN_SEC * 7;
+const DEFAULT_REMINDER_OFFSETS: [u64; 2] = [DAY_IN_SEC * 3, DAY_IN_SEC];
+const DEFAULT_TRANSFER_MIN_AMOUNT: u64 = 10;
+const DEFAULT_TRANSFER_DAILY_LIMIT: u64 = 500;
+const DEFAULT_FILTER_MIN_LENGTH: u64 = 2;
+const DEFAULT_FILTER_MIN_WORDS: u64 = 1;
+const DEFAULT_CLAWBACK_WINDOW: u64 = MINUTE_IN_SEC;
+
+/// Keys moderators can view and edit without a restart
+pub const EDITABLE_KEYS: [&str; 4] = ["cooldown", "custom_role_fee", "nickname_fee", "zones"];
 
 #[derive(Debug)]
 pub struct Config {
     conn: ConnectionWrapper,
     pub inner: Arc<RwLock<ConfigInner>>,
     pub env: EnvConfig,
+    /// Only one edit at a time, so an edit never overwrites another made in the meantime
+    write_lock: tokio::sync::Mutex<()>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ConfigInner {
     pub cooldown: u64,
     pub service_fee: ServiceFee,
-    pub zones: HashSet<Id<ChannelMarker>>,
+    pub zones: HashMap<Id<ChannelMarker>, Zone>,
+    pub energy_boost: EnergyBoostConfig,
+    /// Number of opens without a rare drop after which one is guaranteed, `0` to disable
+    pub blind_box_pity: u64,
+    /// Minimum seconds between two edits of the same custom role
+    pub role_edit_cooldown: u64,
+    /// Whether owners get a DM when a moderator deletes their custom role
+    pub notify_role_delete: bool,
+    /// Whether reconciliation repairs drifts or only reports them
+    pub reconcile_auto_repair: bool,
+    /// Channel receiving admin reports
+    pub admin_log_channel: Option<Id<ChannelMarker>>,
+    /// Seconds a former booster keeps their custom role untouched after the boost ends
+    pub booster_grace_period: u64,
+    /// Seconds before `expires_at` at which custom role owners get a reminder
+    pub reminder_offsets: Vec<u64>,
+    pub transfer: TransferConfig,
+    /// Whether check-in cooldowns survive a restart
+    pub persist_checkins: bool,
+    pub message_filter: MessageFilterConfig,
+    /// Seconds after a check-in during which deleting its message takes the energy back,
+    /// `0` to disable
+    pub clawback_window: u64,
+}
+
+impl ConfigInner {
+    /// Longest cooldown among the global one and the per-zone ones
+    pub fn longest_cooldown(&self) -> u64 {
+        self.zones
+            .values()
+            .filter_map(|zone| zone.cooldown)
+            .fold(self.cooldown, u64::max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Zone {
+    /// Energy earned per check-in
+    #[serde(default = "Zone::default_energy")]
+    pub energy: u64,
+    /// Overrides the global cooldown when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<u64>,
+}
+
+impl Zone {
+    fn default_energy() -> u64 {
+        1
+    }
+}
+
+impl Default for Zone {
+    fn default() -> Zone {
+        Zone {
+            energy: Zone::default_energy(),
+            cooldown: None,
+        }
+    }
+}
+
+/// Reads `zones` in the per-zone format, or in the former plain set of channels.
+///
+/// The flag tells whether the value was in the former format.
+fn parse_zones(value: &str) -> Option<(HashMap<Id<ChannelMarker>, Zone>, bool)> {
+    if let Ok(zones) = serde_json::from_str(value) {
+        return Some((zones, false));
+    }
+
+    let channels = serde_json::from_str::<HashSet<Id<ChannelMarker>>>(value).ok()?;
+    let zones = channels
+        .into_iter()
+        .map(|channel_id| (channel_id, Zone::default()))
+        .collect();
+    Some((zones, true))
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct ServiceFee {
     pub custom_role: u64,
     pub nickname: u64,
+    pub energy_boost: u64,
+    pub blind_box: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnergyBoostConfig {
+    /// Check-in earnings are multiplied by this while a boost is active
+    pub multiplier: u64,
+    /// Lifetime of a single boost in seconds
+    pub duration: u64,
+}
+
+/// Rules a message has to pass to count as a check-in
+#[derive(Debug, Clone, Copy)]
+pub struct MessageFilterConfig {
+    pub ignore_bots: bool,
+    pub ignore_webhooks: bool,
+    /// Joins, pins, boosts and other messages sent by Discord
+    pub ignore_system: bool,
+    /// Minimum characters, `0` to disable
+    pub min_length: u64,
+    /// Minimum distinct words, `0` to disable
+    pub min_words: u64,
+    /// Rejects messages too close to the previous one of the same user
+    pub reject_duplicates: bool,
+    /// Rejects messages made only of stickers or emojis
+    pub reject_stickers_emojis: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransferConfig {
+    /// Smallest amount that can be given at once
+    pub min_amount: u64,
+    /// Most energy a member can give away within 24 hours, `0` for no limit
+    pub daily_limit: u64,
+    /// Percentage of every transfer paid to the community pot
+    pub tax: u64,
+}
+
+impl TransferConfig {
+    pub fn tax_of(&self, amount: u64) -> u64 {
+        amount * self.tax.min(100) / 100
+    }
 }
 
 impl Config {
     pub async fn new(conn: ConnectionWrapper, env: EnvConfig) -> Result<Config> {
         let inner = Arc::new(RwLock::new(conn.fetch_config().await?));
 
-        Ok(Config { conn, inner, env })
+        Ok(Config {
+            conn,
+            inner,
+            env,
+            write_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Current value of an editable key, as stored in `app_config`
+    pub fn value_of(&self, key: &str) -> Option<String> {
+        let config = self.read().unwrap();
+        Some(match key {
+            "cooldown" => config.cooldown.to_string(),
+            "custom_role_fee" => config.service_fee.custom_role.to_string(),
+            "nickname_fee" => config.service_fee.nickname.to_string(),
+            "zones" => serde_json::to_string(&config.zones).ok()?,
+            _ => return None,
+        })
+    }
+
+    /// Applies a new value to an editable key and writes it back to `app_config`.
+    ///
+    /// Returns the value as stored.
+    pub async fn set(&self, key: &str, value: &str) -> Result<String> {
+        let invalid = || anyhow!("`{value}` isn't a valid value for `{key}`");
+        let number = || value.trim().parse::<u64>().map_err(|_| invalid());
+        let _guard = self.write_lock.lock().await;
+
+        match key {
+            "cooldown" => {
+                let cooldown = number()?;
+                self.conn.update_config(key, &cooldown.to_string()).await?;
+                self.write().unwrap().cooldown = cooldown;
+            }
+            "custom_role_fee" => {
+                let fee = number()?;
+                self.conn.update_config(key, &fee.to_string()).await?;
+                self.write().unwrap().service_fee.custom_role = fee;
+            }
+            "nickname_fee" => {
+                let fee = number()?;
+                self.conn.update_config(key, &fee.to_string()).await?;
+                self.write().unwrap().service_fee.nickname = fee;
+            }
+            "zones" => {
+                let (zones, _) = parse_zones(value).ok_or_else(invalid)?;
+                self.conn
+                    .update_config(key, &serde_json::to_string(&zones)?)
+                    .await?;
+                self.write().unwrap().zones = zones;
+            }
+            _ => return Err(anyhow!("`{key}` can't be edited")),
+        }
+
+        Ok(self.value_of(key).unwrap_or_default())
+    }
+
+    /// Edits the zones and writes them back to `app_config`.
+    pub async fn update_zones(
+        &self,
+        update: impl FnOnce(&mut HashMap<Id<ChannelMarker>, Zone>),
+    ) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let mut zones = self.read().unwrap().zones.clone();
+        update(&mut zones);
+
+        self.conn
+            .update_config("zones", &serde_json::to_string(&zones)?)
+            .await?;
+        self.write().unwrap().zones = zones;
+        Ok(())
     }
 }
 
@@ -50,7 +259,32 @@ pub struct ConfigWrapperBuilder {
     pub cooldown: Option<u64>,
     pub custom_role_fee: Option<u64>,
     pub nickname_fee: Option<u64>,
-    pub zones: Option<HashSet<Id<ChannelMarker>>>,
+    pub zones: Option<HashMap<Id<ChannelMarker>, Zone>>,
+    /// Whether `zones` was stored in the former set format and needs to be rewritten
+    pub zones_legacy: bool,
+    pub energy_boost_fee: Option<u64>,
+    pub energy_boost_multiplier: Option<u64>,
+    pub energy_boost_duration: Option<u64>,
+    pub blind_box_fee: Option<u64>,
+    pub blind_box_pity: Option<u64>,
+    pub role_edit_cooldown: Option<u64>,
+    pub notify_role_delete: Option<bool>,
+    pub reconcile_auto_repair: Option<bool>,
+    pub admin_log_channel: Option<Id<ChannelMarker>>,
+    pub booster_grace_period: Option<u64>,
+    pub reminder_offsets: Option<Vec<u64>>,
+    pub transfer_min_amount: Option<u64>,
+    pub transfer_daily_limit: Option<u64>,
+    pub transfer_tax: Option<u64>,
+    pub persist_checkins: Option<bool>,
+    pub filter_bots: Option<bool>,
+    pub filter_webhooks: Option<bool>,
+    pub filter_system: Option<bool>,
+    pub filter_min_length: Option<u64>,
+    pub filter_min_words: Option<u64>,
+    pub filter_duplicates: Option<bool>,
+    pub filter_stickers_emojis: Option<bool>,
+    pub clawback_window: Option<u64>,
 }
 
 impl ConfigWrapperBuilder {
@@ -59,7 +293,34 @@ impl ConfigWrapperBuilder {
             "cooldown" => self.cooldown = value.parse().ok(),
             "custom_role_fee" => self.custom_role_fee = value.parse().ok(),
             "nickname_fee" => self.nickname_fee = value.parse().ok(),
-            "zones" => self.zones = serde_json::from_str(value).ok(),
+            "zones" => {
+                let zones = parse_zones(value);
+                self.zones_legacy = zones.as_ref().is_some_and(|(_, legacy)| *legacy);
+                self.zones = zones.map(|(zones, _)| zones);
+            }
+            "energy_boost_fee" => self.energy_boost_fee = value.parse().ok(),
+            "energy_boost_multiplier" => self.energy_boost_multiplier = value.parse().ok(),
+            "energy_boost_duration" => self.energy_boost_duration = value.parse().ok(),
+            "blind_box_fee" => self.blind_box_fee = value.parse().ok(),
+            "blind_box_pity" => self.blind_box_pity = value.parse().ok(),
+            "role_edit_cooldown" => self.role_edit_cooldown = value.parse().ok(),
+            "notify_role_delete" => self.notify_role_delete = value.parse().ok(),
+            "reconcile_auto_repair" => self.reconcile_auto_repair = value.parse().ok(),
+            "admin_log_channel" => self.admin_log_channel = value.parse().ok(),
+            "booster_grace_period" => self.booster_grace_period = value.parse().ok(),
+            "reminder_offsets" => self.reminder_offsets = serde_json::from_str(value).ok(),
+            "transfer_min_amount" => self.transfer_min_amount = value.parse().ok(),
+            "transfer_daily_limit" => self.transfer_daily_limit = value.parse().ok(),
+            "transfer_tax" => self.transfer_tax = value.parse().ok(),
+            "persist_checkins" => self.persist_checkins = value.parse().ok(),
+            "filter_bots" => self.filter_bots = value.parse().ok(),
+            "filter_webhooks" => self.filter_webhooks = value.parse().ok(),
+            "filter_system" => self.filter_system = value.parse().ok(),
+            "filter_min_length" => self.filter_min_length = value.parse().ok(),
+            "filter_min_words" => self.filter_min_words = value.parse().ok(),
+            "filter_duplicates" => self.filter_duplicates = value.parse().ok(),
+            "filter_stickers_emojis" => self.filter_stickers_emojis = value.parse().ok(),
+            "clawback_window" => self.clawback_window = value.parse().ok(),
             _ => {}
         };
     }
@@ -71,8 +332,51 @@ impl ConfigWrapperBuilder {
                 service_fee: ServiceFee {
                     custom_role: this.custom_role_fee?,
                     nickname: this.nickname_fee?,
+                    energy_boost: this.energy_boost_fee.unwrap_or(DEFAULT_ENERGY_BOOST_FEE),
+                    blind_box: this.blind_box_fee.unwrap_or(DEFAULT_BLIND_BOX_FEE),
                 },
                 zones: this.zones?,
+                energy_boost: EnergyBoostConfig {
+                    multiplier: this
+                        .energy_boost_multiplier
+                        .unwrap_or(DEFAULT_ENERGY_BOOST_MULTIPLIER),
+                    duration: this
+                        .energy_boost_duration
+                        .unwrap_or(DEFAULT_ENERGY_BOOST_DURATION),
+                },
+                blind_box_pity: this.blind_box_pity.unwrap_or(DEFAULT_BLIND_BOX_PITY),
+                role_edit_cooldown: this
+                    .role_edit_cooldown
+                    .unwrap_or(DEFAULT_ROLE_EDIT_COOLDOWN),
+                notify_role_delete: this.notify_role_delete.unwrap_or(true),
+                reconcile_auto_repair: this.reconcile_auto_repair.unwrap_or(false),
+                admin_log_channel: this.admin_log_channel,
+                booster_grace_period: this
+                    .booster_grace_period
+                    .unwrap_or(DEFAULT_BOOSTER_GRACE_PERIOD),
+                reminder_offsets: this
+                    .reminder_offsets
+                    .unwrap_or_else(|| DEFAULT_REMINDER_OFFSETS.to_vec()),
+                transfer: TransferConfig {
+                    min_amount: this
+                        .transfer_min_amount
+                        .unwrap_or(DEFAULT_TRANSFER_MIN_AMOUNT),
+                    daily_limit: this
+                        .transfer_daily_limit
+                        .unwrap_or(DEFAULT_TRANSFER_DAILY_LIMIT),
+                    tax: this.transfer_tax.unwrap_or(0),
+                },
+                persist_checkins: this.persist_checkins.unwrap_or(false),
+                message_filter: MessageFilterConfig {
+                    ignore_bots: this.filter_bots.unwrap_or(true),
+                    ignore_webhooks: this.filter_webhooks.unwrap_or(true),
+                    ignore_system: this.filter_system.unwrap_or(true),
+                    min_length: this.filter_min_length.unwrap_or(DEFAULT_FILTER_MIN_LENGTH),
+                    min_words: this.filter_min_words.unwrap_or(DEFAULT_FILTER_MIN_WORDS),
+                    reject_duplicates: this.filter_duplicates.unwrap_or(true),
+                    reject_stickers_emojis: this.filter_stickers_emojis.unwrap_or(true),
+                },
+                clawback_window: this.clawback_window.unwrap_or(DEFAULT_CLAWBACK_WINDOW),
             })
         }
         inner(self).ok_or_else(|| anyhow!("Config could not be built"))
@@ -1,17 +1,20 @@
 use std::{
+    collections::HashMap,
     fmt::{Debug, Display},
     ops::Deref,
+    str::FromStr,
 };
 
 use anyhow::{Result, anyhow};
 use libsql::{Connection, Database, Value, named_params};
 use serde::Deserialize;
-use tracing::warn;
+use tracing::{info, warn};
 use twilight_mention::{
     Mention,
     timestamp::{Timestamp, TimestampStyle},
 };
 use twilight_model::{
+    guild::Role,
     id::{
         Id,
         marker::{RoleMarker, UserMarker},
@@ -20,9 +23,14 @@ use twilight_model::{
 };
 use twilight_util::snowflake::Snowflake;
 
-use crate::core::{
-    cache::EnergyData,
-    config::{ConfigInner, ConfigWrapperBuilder},
+use crate::{
+    consts::COMMUNITY_POT_ID,
+    core::{
+        app_state::CheckinKey,
+        blind_box::{LootEntry, LootTable, Reward, Roll},
+        cache::EnergyData,
+        config::{ConfigInner, ConfigWrapperBuilder},
+    },
 };
 
 #[derive(Debug)]
@@ -50,6 +58,20 @@ pub struct CustomRole {
     pub mentionable: bool,
 }
 
+impl CustomRole {
+    /// Copies the cosmetic fields of the matching guild role.
+    pub fn apply_role(&mut self, role: Role) {
+        self.name = role.name;
+        self.color = role.color;
+        self.mentionable = role.mentionable;
+        self.icon = role
+            .icon
+            .map(RoleIcon::Custom)
+            .or(role.unicode_emoji.map(RoleIcon::Unicode))
+            .unwrap_or_default();
+    }
+}
+
 impl Display for CustomRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         const EXP_MAX: u64 = 8640000000000;
@@ -99,6 +121,206 @@ impl Display for RoleIcon {
     }
 }
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct EnergyBoost {
+    pub user_id: Id<UserMarker>,
+    pub multiplier: u64,
+    pub expires_at: u64,
+}
+
+impl EnergyBoost {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at > now
+    }
+
+    /// Buys another boost on top of `current`.
+    ///
+    /// Boosts never stack: an active boost is extended by `duration` and keeps the
+    /// higher of the two multipliers, an expired one is simply replaced.
+    pub fn extend(
+        current: Option<EnergyBoost>,
+        user_id: Id<UserMarker>,
+        multiplier: u64,
+        duration: u64,
+        now: u64,
+    ) -> EnergyBoost {
+        match current {
+            Some(boost) if boost.is_active(now) => EnergyBoost {
+                user_id,
+                multiplier: boost.multiplier.max(multiplier),
+                expires_at: boost.expires_at + duration,
+            },
+            _ => EnergyBoost {
+                user_id,
+                multiplier,
+                expires_at: now + duration,
+            },
+        }
+    }
+}
+
+/// Why an energy balance changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerReason {
+    /// Balance carried over from before the ledger existed
+    Opening,
+    Checkin,
+    Purchase,
+    Refund,
+    Renewal,
+    Reward,
+    AdminGrant,
+    AdminRevoke,
+    Transfer,
+    /// Check-in taken back because its message was deleted
+    Clawback,
+}
+
+impl LedgerReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LedgerReason::Opening => "opening",
+            LedgerReason::Checkin => "checkin",
+            LedgerReason::Purchase => "purchase",
+            LedgerReason::Refund => "refund",
+            LedgerReason::Renewal => "renewal",
+            LedgerReason::Reward => "reward",
+            LedgerReason::AdminGrant => "admin_grant",
+            LedgerReason::AdminRevoke => "admin_revoke",
+            LedgerReason::Transfer => "transfer",
+            LedgerReason::Clawback => "clawback",
+        }
+    }
+}
+
+impl FromStr for LedgerReason {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "opening" => LedgerReason::Opening,
+            "checkin" => LedgerReason::Checkin,
+            "purchase" => LedgerReason::Purchase,
+            "refund" => LedgerReason::Refund,
+            "renewal" => LedgerReason::Renewal,
+            "reward" => LedgerReason::Reward,
+            "admin_grant" => LedgerReason::AdminGrant,
+            "admin_revoke" => LedgerReason::AdminRevoke,
+            "transfer" => LedgerReason::Transfer,
+            "clawback" => LedgerReason::Clawback,
+            _ => return Err(anyhow!("Unknown ledger reason `{s}`")),
+        })
+    }
+}
+
+impl Display for LedgerReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerReason::Opening => write!(f, "Opening balance"),
+            LedgerReason::Checkin => write!(f, "Check-in"),
+            LedgerReason::Purchase => write!(f, "Purchase"),
+            LedgerReason::Refund => write!(f, "Refund"),
+            LedgerReason::Renewal => write!(f, "Renewal"),
+            LedgerReason::Reward => write!(f, "Reward"),
+            LedgerReason::AdminGrant => write!(f, "Admin grant"),
+            LedgerReason::AdminRevoke => write!(f, "Admin revoke"),
+            LedgerReason::Transfer => write!(f, "Transfer"),
+            LedgerReason::Clawback => write!(f, "Clawback"),
+        }
+    }
+}
+
+/// A single change of a user's energy balance
+#[derive(Debug, Clone, Copy)]
+pub struct LedgerEntry {
+    pub user_id: Id<UserMarker>,
+    pub delta: i64,
+    pub reason: LedgerReason,
+    /// Id of what caused the change: a message, a role, a moderator, the other party...
+    pub reference: Option<u64>,
+    pub created_at: u64,
+}
+
+impl LedgerEntry {
+    pub fn new(
+        user_id: Id<UserMarker>,
+        delta: i64,
+        reason: LedgerReason,
+        reference: Option<u64>,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            user_id,
+            delta,
+            reason,
+            reference,
+            created_at: std::time::UNIX_EPOCH.elapsed().unwrap().as_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardKind {
+    /// Current balance
+    Balance,
+    /// Energy earned from check-ins and rewards over time
+    Lifetime,
+}
+
+impl LeaderboardKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LeaderboardKind::Balance => "balance",
+            LeaderboardKind::Lifetime => "lifetime",
+        }
+    }
+
+    /// Query yielding the `user_id` and `score` of every ranked member
+    fn scores(self) -> &'static str {
+        match self {
+            LeaderboardKind::Balance => {
+                "SELECT user_id, energy AS score FROM energy_balance WHERE energy > 0"
+            }
+            LeaderboardKind::Lifetime => {
+                "SELECT user_id, SUM(delta) AS score FROM energy_ledger \
+                WHERE reason IN ('opening', 'checkin', 'reward', 'clawback') GROUP BY user_id HAVING score > 0"
+            }
+        }
+    }
+}
+
+impl FromStr for LeaderboardKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "balance" => LeaderboardKind::Balance,
+            "lifetime" => LeaderboardKind::Lifetime,
+            _ => return Err(anyhow!("Unknown leaderboard `{s}`")),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LeaderboardEntry {
+    pub user_id: Id<UserMarker>,
+    pub score: u64,
+    pub rank: u64,
+}
+
+/// Items kept in the `inventory` table, keyed by their stored name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryItem {
+    Nickname,
+}
+
+impl InventoryItem {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InventoryItem::Nickname => "nickname",
+        }
+    }
+}
+
 impl DatabaseClient {
     pub async fn new(
         url: impl Into<String>,
@@ -140,8 +362,27 @@ impl ConnectionWrapper {
         Ok(ConnectionWrapper(connection))
     }
 
-    pub async fn sync_energy_data(&self, items: Vec<EnergyData>) -> Result<()> {
+    /// Fresh in-memory database with the schema applied.
+    #[cfg(test)]
+    pub async fn in_memory() -> Result<ConnectionWrapper> {
+        let database = libsql::Builder::new_local(":memory:").build().await?;
+        let connection = database.connect()?;
+        connection
+            .execute_batch(include_str!("../../schema.sql"))
+            .await?;
+        Ok(ConnectionWrapper(connection))
+    }
+
+    pub async fn sync_energy_data(
+        &self,
+        items: &[EnergyData],
+        ledger: &[LedgerEntry],
+    ) -> Result<()> {
         let tx = self.0.transaction().await?;
+        for entry in ledger {
+            insert_ledger_entry(&tx, entry).await?;
+        }
+
         let mut prep_stmt = tx
             .prepare(
                 "INSERT OR REPLACE INTO energy_balance(user_id, energy) VALUES(:user_id, :energy)",
@@ -150,44 +391,178 @@ impl ConnectionWrapper {
 
         for EnergyData {
             user_id, energy, ..
-        } in items
+        } in items.iter().copied()
         {
-            let _ = prep_stmt
+            prep_stmt
                 .execute(named_params! {":user_id": user_id.get(), ":energy": energy})
-                .await;
+                .await?;
         }
         tx.commit().await?;
 
         Ok(())
     }
 
-    pub async fn sync_energy_one(&self, item: EnergyData) -> Result<()> {
-        let EnergyData {
-            user_id,
-            energy,
-            is_dirty,
-        } = item;
+    pub async fn fetch_energy(&self, user_id: Id<UserMarker>) -> Result<u64> {
+        let rs = self
+            .0
+            .query(
+                "SELECT energy FROM energy_balance WHERE user_id = :user_id",
+                named_params! {":user_id": user_id.get()},
+            )
+            .await?
+            .next()
+            .await?;
+        let Some(row) = rs else {
+            return Ok(0);
+        };
+
+        let idx = (0..row.column_count())
+            .find(|&idx| row.column_name(idx) == Some("energy"))
+            .ok_or_else(|| anyhow!("Could not find column `energy`"))?;
+        let energy = row.get(idx)?;
+        Ok(energy)
+    }
+
+    pub async fn fetch_ledger(
+        &self,
+        user_id: Id<UserMarker>,
+        limit: u64,
+    ) -> Result<Vec<LedgerEntry>> {
+        let mut rows = self
+            .0
+            .query(
+                "SELECT user_id, delta, reason, reference, created_at FROM energy_ledger \
+                WHERE user_id = :user_id ORDER BY entry_id DESC LIMIT :limit",
+                named_params! {":user_id": user_id.get(), ":limit": limit},
+            )
+            .await?;
+        let mut collection = vec![];
 
-        if !is_dirty {
-            return Ok(());
+        while let Some(row) = rows.next().await? {
+            let entry_rs = (|| {
+                anyhow::Ok(LedgerEntry {
+                    user_id: Id::new(row.get(0)?),
+                    delta: row.get(1)?,
+                    reason: row.get_str(2)?.parse()?,
+                    reference: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })();
+            let Ok(entry) = entry_rs else {
+                warn!(source = ?entry_rs.unwrap_err(), "Unable to parse ledger entry");
+                continue;
+            };
+            collection.push(entry);
         }
 
-        self.0
+        Ok(collection)
+    }
+
+    /// Number of members on the leaderboard
+    pub async fn fetch_leaderboard_size(&self, kind: LeaderboardKind) -> Result<u64> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM ({}) WHERE user_id != :pot",
+            kind.scores()
+        );
+        let rs = self
+            .0
+            .query(&sql, named_params! {":pot": COMMUNITY_POT_ID.get()})
+            .await?
+            .next()
+            .await?;
+        let Some(row) = rs else {
+            return Ok(0);
+        };
+
+        Ok(row.get(0)?)
+    }
+
+    pub async fn fetch_leaderboard(
+        &self,
+        kind: LeaderboardKind,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        let sql = format!(
+            "SELECT user_id, score FROM ({}) WHERE user_id != :pot \
+            ORDER BY score DESC, user_id LIMIT :limit OFFSET :offset",
+            kind.scores()
+        );
+        let mut rows = self
+            .0
             .query(
-                "INSERT OR REPLACE INTO energy_balance(user_id, energy) VALUES(:user_id, :energy)",
-                named_params! {":user_id": user_id.get(), ":energy": energy},
+                &sql,
+                named_params! {
+                    ":pot": COMMUNITY_POT_ID.get(),
+                    ":limit": limit,
+                    ":offset": offset,
+                },
             )
             .await?;
+        let mut collection = vec![];
 
-        Ok(())
+        while let Some(row) = rows.next().await? {
+            collection.push(LeaderboardEntry {
+                user_id: Id::new(row.get(0)?),
+                score: row.get(1)?,
+                rank: offset + collection.len() as u64 + 1,
+            });
+        }
+
+        Ok(collection)
     }
 
-    pub async fn fetch_energy(&self, user_id: Id<UserMarker>) -> Result<u64> {
+    /// Returns the leaderboard entry of the user, if they are ranked.
+    pub async fn fetch_leaderboard_rank(
+        &self,
+        kind: LeaderboardKind,
+        user_id: Id<UserMarker>,
+    ) -> Result<Option<LeaderboardEntry>> {
+        // Ties are broken by user id, the same way the leaderboard is ordered
+        let sql = format!(
+            "WITH scores AS ({}) \
+            SELECT me.score, ( \
+                SELECT COUNT(*) FROM scores other WHERE other.user_id != :pot \
+                AND (other.score > me.score OR (other.score = me.score AND other.user_id < me.user_id)) \
+            ) + 1 FROM scores me WHERE me.user_id = :user_id",
+            kind.scores()
+        );
         let rs = self
             .0
             .query(
-                "SELECT energy FROM energy_balance WHERE user_id = :user_id",
-                named_params! {":user_id": user_id.get()},
+                &sql,
+                named_params! {":pot": COMMUNITY_POT_ID.get(), ":user_id": user_id.get()},
+            )
+            .await?
+            .next()
+            .await?;
+        let Some(row) = rs else {
+            return Ok(None);
+        };
+
+        Ok(Some(LeaderboardEntry {
+            user_id,
+            score: row.get(0)?,
+            rank: row.get(1)?,
+        }))
+    }
+
+    /// Returns how much energy the user gave away since `since`.
+    pub async fn fetch_transferred_since(
+        &self,
+        user_id: Id<UserMarker>,
+        since: u64,
+    ) -> Result<u64> {
+        let rs = self
+            .0
+            .query(
+                "SELECT COALESCE(-SUM(delta), 0) FROM energy_ledger \
+                WHERE user_id = :user_id AND reason = :reason AND delta < 0 AND created_at >= :since",
+                named_params! {
+                    ":user_id": user_id.get(),
+                    ":reason": LedgerReason::Transfer.as_str(),
+                    ":since": since,
+                },
             )
             .await?
             .next()
@@ -196,22 +571,25 @@ impl ConnectionWrapper {
             return Ok(0);
         };
 
-        let idx = (0..row.column_count())
-            .find(|&idx| row.column_name(idx) == Some("energy"))
-            .ok_or_else(|| anyhow!("Could not find column `energy`"))?;
-        let energy = row.get(idx)?;
-        Ok(energy)
+        Ok(row.get(0)?)
     }
 
-    pub async fn consume_energy(&self, user_id: Id<UserMarker>, amount: u64) -> Result<bool> {
-        let changes = self
+    /// Returns the balance as derived from the ledger.
+    pub async fn fetch_ledger_balance(&self, user_id: Id<UserMarker>) -> Result<i64> {
+        let rs = self
             .0
-            .execute(
-                "UPDATE energy_balance SET energy = energy - :amount WHERE user_id = :user_id AND energy >= :amount",
-                named_params! {":user_id": user_id.get(), ":amount": amount},
+            .query(
+                "SELECT COALESCE(SUM(delta), 0) FROM energy_ledger WHERE user_id = :user_id",
+                named_params! {":user_id": user_id.get()},
             )
+            .await?
+            .next()
             .await?;
-        Ok(changes != 0)
+        let Some(row) = rs else {
+            return Ok(0);
+        };
+
+        Ok(row.get(0)?)
     }
 
     pub async fn fetch_custom_roles(&self) -> Result<Vec<CustomRole>> {
@@ -308,7 +686,7 @@ impl ConnectionWrapper {
             .execute(
                 "INSERT OR REPLACE INTO custom_roles (role_id, user_id, expires_at, auto_renewal) VALUES (:role_id, :user_id, :expires_at, :auto_renewal)",
                 named_params! {
-                    ":role_id": role.user_id.get(),
+                    ":role_id": role.role_id.get(),
                     ":user_id": role.user_id.get(),
                     ":expires_at": role.expires_at.map(Value::try_from).unwrap_or(Ok(Value::Null))?,
                     ":auto_renewal": role.auto_renewal,
@@ -318,6 +696,286 @@ impl ConnectionWrapper {
         Ok(affected_rows != 0)
     }
 
+    pub async fn fetch_energy_boosts(&self) -> Result<Vec<EnergyBoost>> {
+        let mut rows = self.0.query("SELECT * FROM energy_boosts", ()).await?;
+        let mut collection = vec![];
+
+        while let Some(row) = rows.next().await? {
+            let data_rs = libsql::de::from_row(&row);
+            let Ok(data) = data_rs else {
+                warn!(source = ?data_rs.unwrap_err(), "Unable to parse energy boost");
+                continue;
+            };
+            collection.push(data);
+        }
+
+        Ok(collection)
+    }
+
+    pub async fn update_energy_boost(&self, boost: &EnergyBoost) -> Result<bool> {
+        let affected_rows = self
+            .0
+            .execute(
+                "INSERT OR REPLACE INTO energy_boosts (user_id, multiplier, expires_at) VALUES (:user_id, :multiplier, :expires_at)",
+                named_params! {
+                    ":user_id": boost.user_id.get(),
+                    ":multiplier": boost.multiplier,
+                    ":expires_at": boost.expires_at,
+                },
+            )
+            .await?;
+        Ok(affected_rows != 0)
+    }
+
+    pub async fn fetch_loot_table(&self) -> Result<LootTable> {
+        let mut rows = self
+            .0
+            .query(
+                "SELECT loot_id, weight, rarity, reward, amount FROM loot_table",
+                (),
+            )
+            .await?;
+        let mut entries = vec![];
+
+        while let Some(row) = rows.next().await? {
+            let entry_rs = (|| {
+                anyhow::Ok(LootEntry {
+                    loot_id: row.get(0)?,
+                    weight: row.get(1)?,
+                    rarity: row.get_str(2)?.parse()?,
+                    reward: Reward::new(row.get_str(3)?, row.get(4)?)?,
+                })
+            })();
+            let Ok(entry) = entry_rs else {
+                warn!(source = ?entry_rs.unwrap_err(), "Unable to parse loot entry");
+                continue;
+            };
+            entries.push(entry);
+        }
+
+        Ok(LootTable::new(entries))
+    }
+
+    pub async fn fetch_blind_box_pity(&self, user_id: Id<UserMarker>) -> Result<u64> {
+        let rs = self
+            .0
+            .query(
+                "SELECT counter FROM blind_box_pity WHERE user_id = :user_id",
+                named_params! {":user_id": user_id.get()},
+            )
+            .await?
+            .next()
+            .await?;
+        let Some(row) = rs else {
+            return Ok(0);
+        };
+
+        Ok(row.get(0)?)
+    }
+
+    pub async fn update_blind_box_pity(&self, user_id: Id<UserMarker>, counter: u64) -> Result<()> {
+        self.0
+            .execute(
+                "INSERT OR REPLACE INTO blind_box_pity (user_id, counter) VALUES (:user_id, :counter)",
+                named_params! {":user_id": user_id.get(), ":counter": counter},
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Records `roll` for auditing.
+    pub async fn record_blind_box_roll(
+        &self,
+        user_id: Id<UserMarker>,
+        roll: &Roll,
+        rolled_at: u64,
+    ) -> Result<()> {
+        self.0.execute(
+            "INSERT INTO blind_box_rolls (user_id, loot_id, rarity, pity, rolled_at) VALUES (:user_id, :loot_id, :rarity, :pity, :rolled_at)",
+            named_params! {
+                ":user_id": user_id.get(),
+                ":loot_id": roll.entry.loot_id,
+                ":rarity": roll.entry.rarity.as_str(),
+                ":pity": roll.pity,
+                ":rolled_at": rolled_at,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the check-ins noted since `since`, per user and cooldown zone.
+    pub async fn fetch_checkins(&self, since: u64) -> Result<Vec<(CheckinKey, u64)>> {
+        let mut rows = self
+            .0
+            .query(
+                "SELECT user_id, zone_id, last_checkin FROM checkin_notes WHERE last_checkin >= :since",
+                named_params! {":since": since},
+            )
+            .await?;
+        let mut collection = vec![];
+
+        while let Some(row) = rows.next().await? {
+            let zone_id = Id::new_checked(row.get(1)?);
+            collection.push(((Id::new(row.get(0)?), zone_id), row.get(2)?));
+        }
+
+        Ok(collection)
+    }
+
+    /// Saves the given check-ins and drops the ones older than `expired_before`.
+    pub async fn sync_checkins(
+        &self,
+        items: &HashMap<CheckinKey, u64>,
+        expired_before: u64,
+    ) -> Result<()> {
+        let tx = self.0.transaction().await?;
+        for (&(user_id, zone_id), &last_checkin) in items {
+            tx.execute(
+                "INSERT OR REPLACE INTO checkin_notes (user_id, zone_id, last_checkin) VALUES (:user_id, :zone_id, :last_checkin)",
+                named_params! {
+                    ":user_id": user_id.get(),
+                    ":zone_id": zone_id.map_or(0, Id::get),
+                    ":last_checkin": last_checkin,
+                },
+            )
+            .await?;
+        }
+        tx.execute(
+            "DELETE FROM checkin_notes WHERE last_checkin < :expired_before",
+            named_params! {":expired_before": expired_before},
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn fetch_booster_graces(&self) -> Result<Vec<(Id<UserMarker>, u64)>> {
+        let mut rows = self
+            .0
+            .query("SELECT user_id, ends_at FROM booster_grace", ())
+            .await?;
+        let mut collection = vec![];
+
+        while let Some(row) = rows.next().await? {
+            collection.push((Id::new(row.get(0)?), row.get(1)?));
+        }
+
+        Ok(collection)
+    }
+
+    pub async fn update_booster_grace(
+        &self,
+        user_id: Id<UserMarker>,
+        ends_at: u64,
+    ) -> Result<bool> {
+        let affected_rows = self
+            .0
+            .execute(
+                "INSERT OR REPLACE INTO booster_grace (user_id, ends_at) VALUES (:user_id, :ends_at)",
+                named_params! {":user_id": user_id.get(), ":ends_at": ends_at},
+            )
+            .await?;
+        Ok(affected_rows != 0)
+    }
+
+    pub async fn delete_booster_grace(&self, user_id: Id<UserMarker>) -> Result<bool> {
+        let affected_rows = self
+            .0
+            .execute(
+                "DELETE FROM booster_grace WHERE user_id = :user_id",
+                named_params! {":user_id": user_id.get()},
+            )
+            .await?;
+        Ok(affected_rows != 0)
+    }
+
+    /// Marks the reminder as sent, returns `false` if it already was.
+    pub async fn record_custom_role_reminder(
+        &self,
+        role_id: Id<RoleMarker>,
+        expires_at: u64,
+        offset: u64,
+    ) -> Result<bool> {
+        let affected_rows = self
+            .0
+            .execute(
+                "INSERT OR IGNORE INTO custom_role_reminders (role_id, expires_at, reminder_offset) VALUES (:role_id, :expires_at, :offset)",
+                named_params! {
+                    ":role_id": role_id.get(),
+                    ":expires_at": expires_at,
+                    ":offset": offset,
+                },
+            )
+            .await?;
+        Ok(affected_rows != 0)
+    }
+
+    pub async fn delete_stale_custom_role_reminders(&self, now: u64) -> Result<u64> {
+        let affected_rows = self
+            .0
+            .execute(
+                "DELETE FROM custom_role_reminders WHERE expires_at < :now",
+                named_params! {":now": now},
+            )
+            .await?;
+        Ok(affected_rows)
+    }
+
+    pub async fn fetch_inventory_item(
+        &self,
+        user_id: Id<UserMarker>,
+        item: InventoryItem,
+    ) -> Result<u64> {
+        let rs = self
+            .0
+            .query(
+                "SELECT quantity FROM inventory WHERE user_id = :user_id AND item = :item",
+                named_params! {":user_id": user_id.get(), ":item": item.as_str()},
+            )
+            .await?
+            .next()
+            .await?;
+        let Some(row) = rs else {
+            return Ok(0);
+        };
+
+        Ok(row.get(0)?)
+    }
+
+    pub async fn add_inventory_item(
+        &self,
+        user_id: Id<UserMarker>,
+        item: InventoryItem,
+        amount: u64,
+    ) -> Result<()> {
+        self.0
+            .execute(
+                "INSERT INTO inventory (user_id, item, quantity) VALUES (:user_id, :item, :amount) \
+                ON CONFLICT (user_id, item) DO UPDATE SET quantity = quantity + excluded.quantity",
+                named_params! {":user_id": user_id.get(), ":item": item.as_str(), ":amount": amount},
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn consume_inventory_item(
+        &self,
+        user_id: Id<UserMarker>,
+        item: InventoryItem,
+    ) -> Result<bool> {
+        let changes = self
+            .0
+            .execute(
+                "UPDATE inventory SET quantity = quantity - 1 WHERE user_id = :user_id AND item = :item AND quantity > 0",
+                named_params! {":user_id": user_id.get(), ":item": item.as_str()},
+            )
+            .await?;
+        Ok(changes != 0)
+    }
+
     pub async fn fetch_config(&self) -> Result<ConfigInner> {
         let mut rows = self.0.query("SELECT * FROM app_config", ()).await?;
         let mut builder = ConfigWrapperBuilder::default();
@@ -337,6 +995,40 @@ impl ConnectionWrapper {
             builder.set_field(key, value);
         }
 
-        builder.try_build()
+        let zones_legacy = builder.zones_legacy;
+        let config = builder.try_build()?;
+
+        if zones_legacy {
+            self.update_config("zones", &serde_json::to_string(&config.zones)?)
+                .await?;
+            info!("Migrated `zones` to the per-zone format");
+        }
+
+        Ok(config)
+    }
+
+    pub async fn update_config(&self, key: &str, value: &str) -> Result<()> {
+        self.0
+            .execute(
+                "INSERT OR REPLACE INTO app_config (key, value) VALUES (:key, :value)",
+                named_params! {":key": key, ":value": value},
+            )
+            .await?;
+        Ok(())
     }
 }
+
+async fn insert_ledger_entry(conn: &Connection, entry: &LedgerEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO energy_ledger (user_id, delta, reason, reference, created_at) VALUES (:user_id, :delta, :reason, :reference, :created_at)",
+        named_params! {
+            ":user_id": entry.user_id.get(),
+            ":delta": entry.delta,
+            ":reason": entry.reason.as_str(),
+            ":reference": entry.reference.map(Value::try_from).unwrap_or(Ok(Value::Null))?,
+            ":created_at": entry.created_at,
+        },
+    )
+    .await?;
+    Ok(())
+}
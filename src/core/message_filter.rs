@@ -0,0 +1,361 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use lru::LruCache;
+use twilight_model::{
+    channel::{Message, message::MessageType},
+    id::{Id, marker::UserMarker},
+};
+
+use crate::core::config::{ConfigInner, MessageFilterConfig};
+
+/// Users whose last message is remembered for the duplicate rule
+const LAST_MESSAGES_CAPACITY: usize = 1000;
+/// Share of common words from which two messages are duplicates
+const DUPLICATE_SIMILARITY: f64 = 0.8;
+
+/// Rule a message failed to count as a check-in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rejection {
+    Bot,
+    Webhook,
+    System,
+    StickersOrEmojis,
+    TooShort,
+    TooFewWords,
+    Duplicate,
+}
+
+impl Rejection {
+    pub const ALL: [Rejection; 7] = [
+        Rejection::Bot,
+        Rejection::Webhook,
+        Rejection::System,
+        Rejection::StickersOrEmojis,
+        Rejection::TooShort,
+        Rejection::TooFewWords,
+        Rejection::Duplicate,
+    ];
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Bot => write!(f, "Bot"),
+            Rejection::Webhook => write!(f, "Webhook"),
+            Rejection::System => write!(f, "System message"),
+            Rejection::StickersOrEmojis => write!(f, "Only stickers or emojis"),
+            Rejection::TooShort => write!(f, "Too short"),
+            Rejection::TooFewWords => write!(f, "Too few words"),
+            Rejection::Duplicate => write!(f, "Duplicate"),
+        }
+    }
+}
+
+/// Filter chain deciding which zone messages count as check-ins
+#[derive(Debug)]
+pub struct MessageFilter {
+    config: Arc<RwLock<ConfigInner>>,
+    /// Whether the rules reading the content run, it's empty without the message content intent
+    content_rules: bool,
+    /// Words of the last message of each user
+    last_messages: Mutex<LruCache<Id<UserMarker>, HashSet<String>>>,
+    /// Messages that passed, and rejections per rule, since startup
+    stats: Mutex<FilterStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FilterStats {
+    pub passed: u64,
+    pub rejections: HashMap<Rejection, u64>,
+}
+
+impl MessageFilter {
+    pub fn new(config: Arc<RwLock<ConfigInner>>, content_rules: bool) -> MessageFilter {
+        MessageFilter {
+            config,
+            content_rules,
+            last_messages: Mutex::new(LruCache::new(
+                NonZeroUsize::new(LAST_MESSAGES_CAPACITY).unwrap(),
+            )),
+            stats: Default::default(),
+        }
+    }
+
+    /// Runs the message through every enabled rule and counts the outcome.
+    pub fn check(&self, msg: &Message) -> Result<(), Rejection> {
+        let outcome = self.run_rules(msg);
+
+        let mut stats = self.stats.lock().unwrap();
+        match outcome {
+            Ok(()) => stats.passed += 1,
+            Err(rejection) => *stats.rejections.entry(rejection).or_default() += 1,
+        }
+
+        outcome
+    }
+
+    pub fn stats(&self) -> FilterStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn run_rules(&self, msg: &Message) -> Result<(), Rejection> {
+        let rules = self.config.read().unwrap().message_filter;
+
+        if rules.ignore_bots && msg.author.bot {
+            return Err(Rejection::Bot);
+        }
+        if rules.ignore_webhooks && msg.webhook_id.is_some() {
+            return Err(Rejection::Webhook);
+        }
+        if rules.ignore_system && !matches!(msg.kind, MessageType::Regular | MessageType::Reply) {
+            return Err(Rejection::System);
+        }
+        if !self.content_rules {
+            return Ok(());
+        }
+
+        self.run_content_rules(msg.author.id, &msg.content, rules)
+    }
+
+    fn run_content_rules(
+        &self,
+        user_id: Id<UserMarker>,
+        content: &str,
+        rules: MessageFilterConfig,
+    ) -> Result<(), Rejection> {
+        let text = strip_custom_emojis(content);
+        let text = text.trim();
+
+        // Unicode emojis aren't alphanumeric either
+        if rules.reject_stickers_emojis && !text.chars().any(char::is_alphanumeric) {
+            return Err(Rejection::StickersOrEmojis);
+        }
+        if text.chars().count() < rules.min_length as usize {
+            return Err(Rejection::TooShort);
+        }
+
+        let words = distinct_words(text);
+        if words.len() < rules.min_words as usize {
+            return Err(Rejection::TooFewWords);
+        }
+
+        if rules.reject_duplicates {
+            let previous = self
+                .last_messages
+                .lock()
+                .unwrap()
+                .put(user_id, words.clone());
+            if previous.is_some_and(|previous| is_duplicate(&previous, &words)) {
+                return Err(Rejection::Duplicate);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lowercased words of the text, punctuation left out
+fn distinct_words(text: &str) -> HashSet<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn is_duplicate(previous: &HashSet<String>, words: &HashSet<String>) -> bool {
+    let union = previous.union(words).count();
+    if union == 0 {
+        return true;
+    }
+
+    let common = previous.intersection(words).count();
+    common as f64 / union as f64 >= DUPLICATE_SIMILARITY
+}
+
+/// Removes custom emojis, written as `<:name:id>` or `<a:name:id>`.
+fn strip_custom_emojis(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+
+        match candidate
+            .find('>')
+            .filter(|&end| is_custom_emoji(&candidate[1..end]))
+        {
+            Some(end) => rest = &candidate[end + 1..],
+            None => {
+                stripped.push('<');
+                rest = &candidate[1..];
+            }
+        }
+    }
+    stripped.push_str(rest);
+
+    stripped
+}
+
+/// Whether the inside of `<...>` is a custom emoji
+fn is_custom_emoji(inner: &str) -> bool {
+    let inner = inner.strip_prefix('a').unwrap_or(inner);
+    let Some((name, id)) = inner
+        .strip_prefix(':')
+        .and_then(|inner| inner.split_once(':'))
+    else {
+        return false;
+    };
+
+    !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::config::ConfigWrapperBuilder;
+
+    use super::*;
+
+    const RULES: MessageFilterConfig = MessageFilterConfig {
+        ignore_bots: true,
+        ignore_webhooks: true,
+        ignore_system: true,
+        min_length: 0,
+        min_words: 0,
+        reject_duplicates: false,
+        reject_stickers_emojis: false,
+    };
+
+    fn message_filter() -> MessageFilter {
+        let config = ConfigWrapperBuilder {
+            cooldown: Some(0),
+            custom_role_fee: Some(0),
+            nickname_fee: Some(0),
+            zones: Some(HashMap::new()),
+            ..Default::default()
+        }
+        .try_build()
+        .unwrap();
+        MessageFilter::new(Arc::new(RwLock::new(config)), true)
+    }
+
+    fn words(words: &[&str]) -> HashSet<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn duplicates_start_at_the_similarity_threshold() {
+        let previous = words(&["a", "b", "c", "d"]);
+        // 4 common words out of 5
+        assert!(is_duplicate(&previous, &words(&["a", "b", "c", "d", "e"])));
+        // 3 common words out of 4
+        assert!(!is_duplicate(&previous, &words(&["a", "b", "c"])));
+        assert!(!is_duplicate(
+            &previous,
+            &words(&["a", "b", "c", "d", "e", "f"])
+        ));
+        assert!(is_duplicate(&HashSet::new(), &HashSet::new()));
+    }
+
+    #[test]
+    fn only_custom_emojis_are_stripped() {
+        assert_eq!(strip_custom_emojis("hi <:wave:123> there"), "hi  there");
+        assert_eq!(strip_custom_emojis("<a:dance:456>!"), "!");
+        assert_eq!(
+            strip_custom_emojis("<@123> <#456> <@&789>"),
+            "<@123> <#456> <@&789>"
+        );
+        assert_eq!(
+            strip_custom_emojis("<:no space:1> <:x:> <:x:1a>"),
+            "<:no space:1> <:x:> <:x:1a>"
+        );
+        assert_eq!(strip_custom_emojis("a < b > c"), "a < b > c");
+    }
+
+    #[test]
+    fn emoji_only_messages_are_rejected() {
+        let filter = message_filter();
+        let rules = MessageFilterConfig {
+            reject_stickers_emojis: true,
+            ..RULES
+        };
+        let user_id = Id::new(1);
+
+        for content in ["<:wave:123>", "<a:dance:456> 😀 🎉", "", "!!! ..."] {
+            assert_eq!(
+                filter.run_content_rules(user_id, content, rules),
+                Err(Rejection::StickersOrEmojis),
+                "{content}"
+            );
+        }
+        assert_eq!(filter.run_content_rules(user_id, "😀 ok", rules), Ok(()));
+        assert_eq!(filter.run_content_rules(user_id, "<@123>", rules), Ok(()));
+    }
+
+    #[test]
+    fn min_words_counts_distinct_normalized_words() {
+        let filter = message_filter();
+        let rules = MessageFilterConfig {
+            min_words: 2,
+            ..RULES
+        };
+        let user_id = Id::new(1);
+
+        assert_eq!(
+            distinct_words("Hello, hello HELLO! ... <3"),
+            words(&["hello", "3"])
+        );
+        assert_eq!(
+            filter.run_content_rules(user_id, "Hello hello HELLO!", rules),
+            Err(Rejection::TooFewWords)
+        );
+        assert_eq!(
+            filter.run_content_rules(user_id, "hello !!! <:wave:123>", rules),
+            Err(Rejection::TooFewWords)
+        );
+        assert_eq!(
+            filter.run_content_rules(user_id, "Hello, world", rules),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn duplicates_are_per_user() {
+        let filter = message_filter();
+        let rules = MessageFilterConfig {
+            reject_duplicates: true,
+            ..RULES
+        };
+        let (first, second) = (Id::new(1), Id::new(2));
+
+        assert_eq!(
+            filter.run_content_rules(first, "good morning all", rules),
+            Ok(())
+        );
+        assert_eq!(
+            filter.run_content_rules(second, "Good morning, all!", rules),
+            Ok(())
+        );
+        assert_eq!(
+            filter.run_content_rules(first, "GOOD MORNING ALL", rules),
+            Err(Rejection::Duplicate)
+        );
+        assert_eq!(
+            filter.run_content_rules(first, "see you later", rules),
+            Ok(())
+        );
+    }
+}
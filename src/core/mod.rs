@@ -1,4 +1,6 @@
 pub mod app_state;
+pub mod blind_box;
 pub mod cache;
 pub mod config;
 pub mod database;
+pub mod message_filter;
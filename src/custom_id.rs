@@ -0,0 +1,284 @@
+use std::{
+    fmt::Display,
+    str::{FromStr, Split},
+};
+
+use anyhow::{Result, anyhow};
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, UserMarker},
+};
+
+use crate::{
+    consts::interact,
+    core::database::LeaderboardKind,
+    events::interaction_create::{CustomRoleField, OrderItem},
+};
+
+/// Bumped whenever an existing id changes its layout, so components sent before are turned down
+const VERSION: u32 = 1;
+/// Longest `custom_id` Discord accepts
+const MAX_LENGTH: usize = 100;
+const SEPARATOR: char = ':';
+
+/// The `custom_id` of a component or a modal, as `v<version>:<tag>[:<payload>...]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomId {
+    Inventory,
+    Shop,
+    Games,
+    About,
+    Faqs,
+    /// Leaderboard opened from the menu
+    Leaderboard,
+    /// Leaderboard page reached from another page
+    LeaderboardPage {
+        kind: LeaderboardKind,
+        page: u64,
+    },
+    CustomRole,
+    CustomRoleSubscribe,
+    CustomRoleUnsubscribe,
+    /// Editor button of a custom role field, and the modal it opens
+    CustomRoleEdit(CustomRoleField),
+    CustomRoleToggleMentionable,
+    ConfirmOrder(OrderItem),
+    ChangeNickname,
+    GiftEnergy(Id<UserMarker>),
+    ZonesAdd,
+    ZonesRemove(Id<ChannelMarker>),
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// Made by another version of the bot, or before ids were versioned
+    Outdated,
+    Malformed(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Outdated => write!(f, "Outdated custom id"),
+            DecodeError::Malformed(custom_id) => write!(f, "Malformed custom id `{custom_id}`"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl CustomId {
+    /// Encodes the id, failing if it doesn't fit in a `custom_id`.
+    pub fn encode(self) -> Result<String> {
+        let tag = self.tag();
+        let custom_id = match self {
+            CustomId::LeaderboardPage { kind, page } => {
+                format!("v{VERSION}:{tag}:{}:{page}", kind.as_str())
+            }
+            CustomId::GiftEnergy(recipient) => format!("v{VERSION}:{tag}:{recipient}"),
+            CustomId::ZonesRemove(channel_id) => format!("v{VERSION}:{tag}:{channel_id}"),
+            _ => format!("v{VERSION}:{tag}"),
+        };
+
+        if custom_id.len() > MAX_LENGTH {
+            return Err(anyhow!(
+                "Custom id `{custom_id}` is longer than {MAX_LENGTH} characters"
+            ));
+        }
+        Ok(custom_id)
+    }
+
+    pub fn decode(custom_id: &str) -> Result<CustomId, DecodeError> {
+        let mut payload = Payload {
+            custom_id,
+            parts: custom_id.split(SEPARATOR),
+        };
+
+        let version = payload
+            .parts
+            .next()
+            .and_then(|version| version.strip_prefix('v'))
+            .and_then(|version| version.parse::<u32>().ok());
+        if version != Some(VERSION) {
+            return Err(DecodeError::Outdated);
+        }
+
+        let tag = payload.parts.next().unwrap_or_default();
+        let decoded = match tag {
+            interact::INVENTORY => CustomId::Inventory,
+            interact::SHOP => CustomId::Shop,
+            interact::GAMES => CustomId::Games,
+            interact::ABOUT => CustomId::About,
+            interact::FAQS => CustomId::Faqs,
+            interact::LEADERBOARD => CustomId::Leaderboard,
+            interact::LEADERBOARD_PAGE => CustomId::LeaderboardPage {
+                kind: payload.next()?,
+                page: payload.next()?,
+            },
+            interact::CUSTOMROLE => CustomId::CustomRole,
+            interact::CUSTOMROLE_SUBSCRIBE => CustomId::CustomRoleSubscribe,
+            interact::CUSTOMROLE_UNSUBSCRIBE => CustomId::CustomRoleUnsubscribe,
+            interact::CUSTOMROLE_EDIT_NAME => CustomId::CustomRoleEdit(CustomRoleField::Name),
+            interact::CUSTOMROLE_EDIT_COLOR => CustomId::CustomRoleEdit(CustomRoleField::Color),
+            interact::CUSTOMROLE_EDIT_ICON => CustomId::CustomRoleEdit(CustomRoleField::Icon),
+            interact::CUSTOMROLE_TOGGLE_MENTIONABLE => CustomId::CustomRoleToggleMentionable,
+            interact::CONFIRM_ORDER_CUSTOMROLE => CustomId::ConfirmOrder(OrderItem::CustomRole),
+            interact::CONFIRM_ORDER_NICKNAME => CustomId::ConfirmOrder(OrderItem::Nickname),
+            interact::CONFIRM_ORDER_ENERGYBOOST => CustomId::ConfirmOrder(OrderItem::EnergyBoost),
+            interact::CONFIRM_ORDER_BLINDBOX => CustomId::ConfirmOrder(OrderItem::BlindBox),
+            interact::CHANGE_NICKNAME => CustomId::ChangeNickname,
+            interact::GIFT_ENERGY_MODAL => CustomId::GiftEnergy(payload.next()?),
+            interact::ZONES_ADD => CustomId::ZonesAdd,
+            interact::ZONES_REMOVE => CustomId::ZonesRemove(payload.next()?),
+            _ => return Err(payload.malformed()),
+        };
+
+        match payload.parts.next() {
+            Some(_) => Err(payload.malformed()),
+            None => Ok(decoded),
+        }
+    }
+
+    fn tag(self) -> &'static str {
+        match self {
+            CustomId::Inventory => interact::INVENTORY,
+            CustomId::Shop => interact::SHOP,
+            CustomId::Games => interact::GAMES,
+            CustomId::About => interact::ABOUT,
+            CustomId::Faqs => interact::FAQS,
+            CustomId::Leaderboard => interact::LEADERBOARD,
+            CustomId::LeaderboardPage { .. } => interact::LEADERBOARD_PAGE,
+            CustomId::CustomRole => interact::CUSTOMROLE,
+            CustomId::CustomRoleSubscribe => interact::CUSTOMROLE_SUBSCRIBE,
+            CustomId::CustomRoleUnsubscribe => interact::CUSTOMROLE_UNSUBSCRIBE,
+            CustomId::CustomRoleEdit(CustomRoleField::Name) => interact::CUSTOMROLE_EDIT_NAME,
+            CustomId::CustomRoleEdit(CustomRoleField::Color) => interact::CUSTOMROLE_EDIT_COLOR,
+            CustomId::CustomRoleEdit(CustomRoleField::Icon) => interact::CUSTOMROLE_EDIT_ICON,
+            CustomId::CustomRoleToggleMentionable => interact::CUSTOMROLE_TOGGLE_MENTIONABLE,
+            CustomId::ConfirmOrder(OrderItem::CustomRole) => interact::CONFIRM_ORDER_CUSTOMROLE,
+            CustomId::ConfirmOrder(OrderItem::Nickname) => interact::CONFIRM_ORDER_NICKNAME,
+            CustomId::ConfirmOrder(OrderItem::EnergyBoost) => interact::CONFIRM_ORDER_ENERGYBOOST,
+            CustomId::ConfirmOrder(OrderItem::BlindBox) => interact::CONFIRM_ORDER_BLINDBOX,
+            CustomId::ChangeNickname => interact::CHANGE_NICKNAME,
+            CustomId::GiftEnergy(_) => interact::GIFT_ENERGY_MODAL,
+            CustomId::ZonesAdd => interact::ZONES_ADD,
+            CustomId::ZonesRemove(_) => interact::ZONES_REMOVE,
+        }
+    }
+}
+
+/// Payload left after the version and the tag
+struct Payload<'a> {
+    custom_id: &'a str,
+    parts: Split<'a, char>,
+}
+
+impl Payload<'_> {
+    fn next<T: FromStr>(&mut self) -> Result<T, DecodeError> {
+        self.parts
+            .next()
+            .and_then(|part| part.parse().ok())
+            .ok_or_else(|| self.malformed())
+    }
+
+    fn malformed(&self) -> DecodeError {
+        DecodeError::Malformed(self.custom_id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_variant() -> Vec<CustomId> {
+        vec![
+            CustomId::Inventory,
+            CustomId::Shop,
+            CustomId::Games,
+            CustomId::About,
+            CustomId::Faqs,
+            CustomId::Leaderboard,
+            CustomId::LeaderboardPage {
+                kind: LeaderboardKind::Balance,
+                page: 0,
+            },
+            CustomId::LeaderboardPage {
+                kind: LeaderboardKind::Lifetime,
+                page: u64::MAX,
+            },
+            CustomId::CustomRole,
+            CustomId::CustomRoleSubscribe,
+            CustomId::CustomRoleUnsubscribe,
+            CustomId::CustomRoleEdit(CustomRoleField::Name),
+            CustomId::CustomRoleEdit(CustomRoleField::Color),
+            CustomId::CustomRoleEdit(CustomRoleField::Icon),
+            CustomId::CustomRoleToggleMentionable,
+            CustomId::ConfirmOrder(OrderItem::CustomRole),
+            CustomId::ConfirmOrder(OrderItem::Nickname),
+            CustomId::ConfirmOrder(OrderItem::EnergyBoost),
+            CustomId::ConfirmOrder(OrderItem::BlindBox),
+            CustomId::ChangeNickname,
+            CustomId::GiftEnergy(Id::new(u64::MAX)),
+            CustomId::ZonesAdd,
+            CustomId::ZonesRemove(Id::new(1)),
+        ]
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        for custom_id in every_variant() {
+            let encoded = custom_id.encode().unwrap();
+            assert!(encoded.len() <= MAX_LENGTH);
+            assert_eq!(CustomId::decode(&encoded).unwrap(), custom_id, "{encoded}");
+        }
+    }
+
+    #[test]
+    fn unversioned_or_other_version_is_outdated() {
+        for custom_id in [
+            "shop", "", "v:shop", "vx:shop", "v0:shop", "v2:shop", "v10:shop",
+        ] {
+            assert!(
+                matches!(CustomId::decode(custom_id), Err(DecodeError::Outdated)),
+                "{custom_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_payload_is_malformed() {
+        for custom_id in [
+            "v1:",
+            "v1:unknown",
+            "v1:shop:",
+            "v1:shop:1",
+            "v1:zones-remove:1:2",
+            "v1:leaderboard-page:balance:1:",
+        ] {
+            assert!(
+                matches!(CustomId::decode(custom_id), Err(DecodeError::Malformed(_))),
+                "{custom_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_or_invalid_payload_is_malformed() {
+        for custom_id in [
+            "v1:gift-energy",
+            "v1:gift-energy:",
+            "v1:gift-energy:0",
+            "v1:gift-energy:someone",
+            "v1:zones-remove",
+            "v1:leaderboard-page",
+            "v1:leaderboard-page:balance",
+            "v1:leaderboard-page:weekly:1",
+            "v1:leaderboard-page:balance:-1",
+        ] {
+            assert!(
+                matches!(CustomId::decode(custom_id), Err(DecodeError::Malformed(_))),
+                "{custom_id}"
+            );
+        }
+    }
+}
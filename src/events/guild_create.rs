@@ -3,9 +3,9 @@ use std::collections::HashMap;
 use anyhow::Result;
 use twilight_model::{gateway::payload::incoming::GuildCreate, guild::Guild};
 
-use crate::core::{app_state::AppState, database::RoleIcon};
+use crate::{core::app_state::AppState, reconcile};
 
-pub fn handle(state: AppState, guild_create: Box<GuildCreate>) -> Result<()> {
+pub async fn handle(state: AppState, guild_create: Box<GuildCreate>) -> Result<()> {
     let GuildCreate::Available(guild) = *guild_create else {
         return Ok(());
     };
@@ -32,16 +32,11 @@ pub fn handle(state: AppState, guild_create: Box<GuildCreate>) -> Result<()> {
         let Some(crole) = custom_roles.get_mut(&role.id) else {
             continue;
         };
-        crole.name = role.name;
-        crole.color = role.color;
-        crole.mentionable = role.mentionable;
-        crole.icon = role
-            .icon
-            .map(RoleIcon::Custom)
-            .or(role.unicode_emoji.map(RoleIcon::Unicode))
-            .unwrap_or_default();
+        crole.apply_role(role);
     }
     drop(user_custom_roles);
 
+    reconcile::run_and_report(&state).await?;
+
     Ok(())
 }
@@ -1,22 +1,35 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, pin::pin};
 
 use anyhow::{Result, anyhow};
+use tokio::sync::watch;
 use twilight_model::{
-    application::interaction::{Interaction, InteractionData},
+    application::{
+        command::CommandType,
+        interaction::{
+            Interaction, InteractionData, InteractionType,
+            application_command::{CommandDataOption, CommandOptionValue},
+        },
+    },
+    channel::message::MessageFlags,
     gateway::payload::incoming::InteractionCreate,
+    guild::{PartialMember, Permissions},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
     id::{
         Id,
-        marker::{ApplicationMarker, InteractionMarker},
+        marker::{ApplicationMarker, ChannelMarker, InteractionMarker, UserMarker},
     },
 };
 
 use crate::{
     consts,
-    core::app_state::AppState,
+    core::{app_state::AppState, database::LeaderboardKind},
+    custom_id::{CustomId, DecodeError},
     interactions::{
-        confirm_inventory_nickname, confirm_order, custom_role, custom_role_subscribe, inventory,
-        inventory_nickname, menu, server_error_response, shop, shop_blind_box, shop_custom_role,
-        shop_energy_boost, shop_nickname, unimplemented,
+        confirm_custom_role_edit, confirm_inventory_nickname, confirm_order, custom_role,
+        custom_role_editor, custom_role_subscribe, ephemeral_response, filter_stats, give, inspect,
+        inventory, inventory_nickname, leaderboard, ledger, menu, mod_config, mod_custom_role,
+        mod_energy, reconcile, server_error_response, shop, shop_blind_box, shop_custom_role,
+        shop_energy_boost, shop_nickname, unimplemented, zones,
     },
 };
 
@@ -28,50 +41,209 @@ pub async fn handle(state: AppState, mut interaction: Box<InteractionCreate>) ->
     };
 
     let user_id = interaction.author_id().unwrap();
+    let kind = interaction.kind;
     let interaction_item = InteractionItem::try_from(interaction.0)?;
-    let state1 = state.clone();
+    if let InteractionItem::UnimplementedAbnormal = interaction_item {
+        return Err(anyhow!("Encounter an unhandled abnormal interaction."));
+    }
+
+    // Without a request from the handler, components keep their message and the rest
+    // get a private loading state, the response is fixed up on delivery if needed
+    let fallback = match kind {
+        InteractionType::MessageComponent => Deferral::Update,
+        _ => Deferral::Message { ephemeral: true },
+    };
+    let (defer, mut requested) = Defer::new();
+    let mut handler = pin!(dispatch(state.clone(), user_id, interaction_item, defer));
+    let threshold = tokio::time::sleep(consts::DEFER_THRESHOLD);
+    tokio::pin!(threshold);
+    let mut past_threshold = false;
+
+    let client = state.app.interaction(auth.application_id);
+    let deferral = loop {
+        let deferral = tokio::select! {
+            biased;
+            response_rs = handler.as_mut() => {
+                client
+                    .create_response(
+                        auth.interaction_id,
+                        &auth.interaction_token,
+                        &unwrap_response(response_rs),
+                    )
+                    .await?;
+                return Ok(());
+            }
+            Ok(()) = requested.changed() => *requested.borrow_and_update(),
+            _ = &mut threshold, if !past_threshold => {
+                past_threshold = true;
+                Some(requested.borrow().unwrap_or(fallback))
+            }
+        };
+        match deferral {
+            Some(Deferral::Never) | None => continue,
+            Some(deferral) => break deferral,
+        }
+    };
 
-    let response_rs = match interaction_item {
-        InteractionItem::Menu => menu::run(state1),
-        InteractionItem::Inventory => inventory::run(state1, user_id).await,
+    // The handler may be past a debit already, it runs to completion either way
+    let (deferred, response_rs) = tokio::join!(
+        async {
+            client
+                .create_response(
+                    auth.interaction_id,
+                    &auth.interaction_token,
+                    &deferral.response(),
+                )
+                .await
+        },
+        handler,
+    );
+    let response = unwrap_response(response_rs);
+    deferred?;
+    followup(&state, &auth, deferral, response).await
+}
+
+async fn dispatch(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    interaction_item: InteractionItem,
+    defer: Defer,
+) -> Result<InteractionResponse> {
+    match interaction_item {
+        InteractionItem::Menu => menu::run(state),
+        InteractionItem::Inventory => inventory::run(state, user_id).await,
         InteractionItem::CustomRole | InteractionItem::InventoryCustomRole => {
-            custom_role::run(state1, user_id)
+            custom_role::run(state, user_id)
         }
         InteractionItem::Shop => shop::run(),
-        InteractionItem::ShopCustomRole => shop_custom_role::run(),
-        InteractionItem::ShopNickname => shop_nickname::run(),
-        InteractionItem::ShopEnergyBoost => shop_energy_boost::run(),
-        InteractionItem::ShopBlindBox => shop_blind_box::run(),
-        InteractionItem::InventoryNickname => inventory_nickname::run(),
-        InteractionItem::ConfirmOrder(data) => confirm_order::run(data),
+        InteractionItem::Leaderboard(page) => leaderboard::run(state, user_id, page, &defer).await,
+        InteractionItem::ShopCustomRole => shop_custom_role::run(state, user_id),
+        InteractionItem::ShopNickname => shop_nickname::run(state),
+        InteractionItem::ShopEnergyBoost => shop_energy_boost::run(state),
+        InteractionItem::ShopBlindBox => shop_blind_box::run(state),
+        InteractionItem::InventoryNickname => inventory_nickname::run(state, user_id, &defer).await,
+        InteractionItem::ConfirmOrder(data) => {
+            confirm_order::run(state, data, user_id, &defer).await
+        }
         InteractionItem::ConfirmInventoryNickname(data) => {
-            confirm_inventory_nickname::run(state1, data, user_id).await
+            confirm_inventory_nickname::run(state, data, user_id).await
         }
         InteractionItem::CustomRoleSubscribe => {
-            custom_role_subscribe::run(state.clone(), true).await
+            custom_role_subscribe::run(state, user_id, true).await
         }
         InteractionItem::CustomRoleUnsubscribe => {
-            custom_role_subscribe::run(state.clone(), false).await
+            custom_role_subscribe::run(state, user_id, false).await
         }
-        InteractionItem::Unimplemented => unimplemented::run(),
-        InteractionItem::UnimplementedAbnormal => {
-            Err(anyhow!("Encounter an unhandled abnormal interaction."))?
+        InteractionItem::CustomRoleEditor(field) => {
+            custom_role_editor::run(state, user_id, field, &defer).await
         }
-    };
+        InteractionItem::ConfirmCustomRoleEdit(edit) => {
+            confirm_custom_role_edit::run(state, user_id, edit).await
+        }
+        InteractionItem::GiftEnergy { recipient, to_bot } => give::modal(state, recipient, to_bot),
+        InteractionItem::Give(gift) => give::run(state, user_id, gift).await,
+        InteractionItem::Reconcile => reconcile::run(state, &defer).await,
+        InteractionItem::FilterStats => filter_stats::run(state),
+        InteractionItem::Ledger(target) => ledger::run(state, target, &defer).await,
+        InteractionItem::ConfigView => mod_config::view(state, user_id).await,
+        InteractionItem::ConfigSet { key, value } => {
+            mod_config::set(state, user_id, key, value).await
+        }
+        InteractionItem::GrantEnergy { target, amount } => {
+            mod_energy::grant(state, user_id, target, amount).await
+        }
+        InteractionItem::RevokeEnergy { target, amount } => {
+            mod_energy::revoke(state, user_id, target, amount).await
+        }
+        InteractionItem::ExpireCustomRole(target) => {
+            mod_custom_role::expire(state, user_id, target).await
+        }
+        InteractionItem::ExtendCustomRole { target, days } => {
+            mod_custom_role::extend(state, user_id, target, days).await
+        }
+        InteractionItem::Inspect(target) => inspect::run(state, user_id, target).await,
+        InteractionItem::Zones(action) => zones::run(state, user_id, action).await,
+        InteractionItem::Forbidden => {
+            Ok(ephemeral_response("Only moderators can use this command."))
+        }
+        InteractionItem::Outdated => Ok(ephemeral_response(
+            "This message is outdated, please open it again.",
+        )),
+        InteractionItem::Unimplemented => unimplemented::run(),
+        InteractionItem::UnimplementedAbnormal => unreachable!("Turned down before dispatching"),
+    }
+}
 
-    let response = match response_rs {
+/// Falls back to the generic error message when the handler failed
+fn unwrap_response(response_rs: Result<InteractionResponse>) -> InteractionResponse {
+    match response_rs {
         Ok(response) => response,
         Err(err) => {
             tracing::error!("Failed to handle interaction: {err}");
             server_error_response()
         }
+    }
+}
+
+/// Delivers the response of a deferred interaction.
+///
+/// It replaces the original response, unless the handler answered an update with a new
+/// message, or answered with another visibility than the deferred message. A followup with
+/// the right flags is sent then, and a mismatched loading message is deleted.
+async fn followup(
+    state: &AppState,
+    auth: &InteractionAuth,
+    deferral: Deferral,
+    response: InteractionResponse,
+) -> Result<()> {
+    let client = state.app.interaction(auth.application_id);
+    let data = response.data.unwrap_or_default();
+    let content = data.content.as_deref();
+    let embeds = data.embeds.as_deref().unwrap_or_default();
+    let components = data.components.as_deref().unwrap_or_default();
+    let ephemeral = data
+        .flags
+        .is_some_and(|flags| flags.contains(MessageFlags::EPHEMERAL));
+
+    let as_followup = match (deferral, response.kind) {
+        (_, InteractionResponseType::Modal) => {
+            return Err(anyhow!("A deferred interaction can't open a modal"));
+        }
+        (Deferral::Update, InteractionResponseType::ChannelMessageWithSource) => true,
+        (
+            Deferral::Message {
+                ephemeral: deferred_ephemeral,
+            },
+            InteractionResponseType::ChannelMessageWithSource,
+        ) if deferred_ephemeral != ephemeral => {
+            // Flags of the original response can't be changed once sent
+            client.delete_response(&auth.interaction_token).await?;
+            true
+        }
+        _ => false,
     };
 
-    state
-        .app
-        .interaction(auth.application_id)
-        .create_response(auth.interaction_id, &auth.interaction_token, &response)
-        .await?;
+    if as_followup {
+        let mut request = client
+            .create_followup(&auth.interaction_token)
+            .embeds(embeds)
+            .components(components);
+        if let Some(content) = content {
+            request = request.content(content);
+        }
+        if let Some(flags) = data.flags {
+            request = request.flags(flags);
+        }
+        request.await?;
+    } else {
+        client
+            .update_response(&auth.interaction_token)
+            .content(content)
+            .embeds(Some(embeds))
+            .components(Some(components))
+            .await?;
+    }
+
     Ok(())
 }
 
@@ -94,6 +266,9 @@ enum InteractionItem {
     // View shop
     Shop,
 
+    // Leaderboard pages
+    Leaderboard(LeaderboardPage),
+
     // Purchase custom role
     ShopCustomRole,
 
@@ -124,6 +299,72 @@ enum InteractionItem {
     // Manage custom roles (same as CustomRole, but accessed from inventory)
     InventoryCustomRole,
 
+    // Open the editor of a custom role field
+    CustomRoleEditor(CustomRoleField),
+
+    // Apply an edit to the custom role
+    ConfirmCustomRoleEdit(CustomRoleEdit),
+
+    // Ask for the amount to gift from the user context menu
+    GiftEnergy {
+        recipient: Id<UserMarker>,
+        to_bot: bool,
+    },
+
+    // Move energy to another member
+    Give(Gift),
+
+    // Reconcile the custom roles table with the guild
+    Reconcile,
+
+    // Show how the check-in filters performed
+    FilterStats,
+
+    // Show the recent ledger entries of a user
+    Ledger(Id<UserMarker>),
+
+    // Show the editable config keys
+    ConfigView,
+
+    // Change an editable config key
+    ConfigSet {
+        key: String,
+        value: String,
+    },
+
+    // Give energy to a member
+    GrantEnergy {
+        target: Id<UserMarker>,
+        amount: u64,
+    },
+
+    // Take energy from a member
+    RevokeEnergy {
+        target: Id<UserMarker>,
+        amount: u64,
+    },
+
+    // Remove the custom role of a member now
+    ExpireCustomRole(Id<UserMarker>),
+
+    // Push back the expiry of a member's custom role
+    ExtendCustomRole {
+        target: Id<UserMarker>,
+        days: u64,
+    },
+
+    // Show the economy state of a member
+    Inspect(Id<UserMarker>),
+
+    // View and edit the zones from the admin panel
+    Zones(ZonesAction),
+
+    // Moderator-only interactions used by someone else
+    Forbidden,
+
+    // Components and modals sent by an older version of the bot
+    Outdated,
+
     // Unimplemented interactions
     Unimplemented,
 
@@ -131,6 +372,66 @@ enum InteractionItem {
     UnimplementedAbnormal,
 }
 
+#[derive(Debug, Clone, Copy)]
+enum Deferral {
+    /// The handler may answer with a modal
+    Never,
+    /// Shows a loading state until the response is sent as a new message
+    Message { ephemeral: bool },
+    /// Acknowledges a component whose message gets edited later
+    Update,
+}
+
+impl Deferral {
+    fn response(self) -> InteractionResponse {
+        match self {
+            Deferral::Never => unreachable!("Modal responses can't be deferred"),
+            Deferral::Message { ephemeral } => InteractionResponse {
+                kind: InteractionResponseType::DeferredChannelMessageWithSource,
+                data: ephemeral.then(|| InteractionResponseData {
+                    flags: Some(MessageFlags::EPHEMERAL),
+                    ..Default::default()
+                }),
+            },
+            Deferral::Update => InteractionResponse {
+                kind: InteractionResponseType::DeferredUpdateMessage,
+                data: None,
+            },
+        }
+    }
+}
+
+/// Lets a handler decide how its response gets deferred.
+///
+/// A handler expecting slow work defers before it, with the visibility it's going to answer
+/// with. Otherwise the response is deferred once the handler runs past
+/// [`consts::DEFER_THRESHOLD`], and fixed up on delivery if the visibility turns out wrong.
+#[derive(Debug)]
+pub struct Defer(watch::Sender<Option<Deferral>>);
+
+impl Defer {
+    fn new() -> (Defer, watch::Receiver<Option<Deferral>>) {
+        let (tx, rx) = watch::channel(None);
+        (Defer(tx), rx)
+    }
+
+    /// Defers right away with a loading message, later replaced by the response.
+    pub fn message(&self, ephemeral: bool) {
+        self.0.send_replace(Some(Deferral::Message { ephemeral }));
+    }
+
+    /// Defers right away, the component's message getting edited by the response later.
+    pub fn update(&self) {
+        self.0.send_replace(Some(Deferral::Update));
+    }
+
+    /// Keeps the response from being deferred, for handlers that may answer with a modal
+    /// after awaiting something.
+    pub fn forbid(&self) {
+        self.0.send_replace(Some(Deferral::Never));
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ConfirmOrder {
     pub okay: bool,
@@ -146,7 +447,7 @@ impl ConfirmOrder {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderItem {
     CustomRole,
     Nickname,
@@ -158,12 +459,58 @@ pub struct ConfirmChangeNickname {
     pub nickname: String,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct LeaderboardPage {
+    pub kind: LeaderboardKind,
+    pub page: u64,
+    /// Whether to edit the current message instead of sending a new one
+    pub update: bool,
+}
+
+impl LeaderboardPage {
+    fn first() -> LeaderboardPage {
+        LeaderboardPage {
+            kind: LeaderboardKind::Balance,
+            page: 0,
+            update: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Gift {
+    pub recipient: Id<UserMarker>,
+    /// Bots can't hold energy
+    pub to_bot: bool,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum ZonesAction {
+    View,
+    Add(Vec<Id<ChannelMarker>>),
+    Remove(Id<ChannelMarker>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomRoleField {
+    Name,
+    Color,
+    Icon,
+}
+
+#[derive(Debug, Clone)]
+pub enum CustomRoleEdit {
+    Name(String),
+    Color(String),
+    Icon(String),
+    ToggleMentionable,
+}
+
 impl TryFrom<Interaction> for InteractionItem {
     type Error = anyhow::Error;
 
     fn try_from(interaction: Interaction) -> std::result::Result<Self, Self::Error> {
-        use twilight_model::application::interaction::InteractionType;
-
         match interaction.kind {
             InteractionType::ApplicationCommand => Ok(command_extractor(interaction)?),
             InteractionType::MessageComponent => Ok(component_extractor(interaction)?),
@@ -179,61 +526,227 @@ fn command_extractor(interaction: Interaction) -> Result<InteractionItem> {
     let Some(InteractionData::ApplicationCommand(data)) = interaction.data else {
         return Err(anyhow::anyhow!("Command without data"));
     };
+    let is_bot = |user_id: Id<UserMarker>| {
+        data.resolved
+            .as_ref()
+            .and_then(|resolved| resolved.users.get(&user_id))
+            .is_some_and(|user| user.bot)
+    };
+
     Ok(match data.name.as_str() {
         consts::interact::PIKABOO => InteractionItem::Menu,
+        consts::interact::GIFT_ENERGY if data.kind == CommandType::User => {
+            let Some(target_id) = data.target_id else {
+                return Err(anyhow::anyhow!("User command without target"));
+            };
+            let recipient = target_id.cast();
+            InteractionItem::GiftEnergy {
+                recipient,
+                to_bot: is_bot(recipient),
+            }
+        }
         cmd @ consts::interact::PIKABOO_QUICK => {
-            let Some(name) = data.options.first().map(|op| op.name.as_str()) else {
+            let Some(subcommand) = data.options.first() else {
                 return Err(anyhow::anyhow!("Subcommand is needed: {cmd}"));
             };
+            let name = subcommand.name.as_str();
             match name {
                 consts::interact::INVENTORY => InteractionItem::Inventory,
                 consts::interact::SHOP => InteractionItem::Shop,
                 consts::interact::CUSTOMROLE => InteractionItem::CustomRole,
+                consts::interact::LEADERBOARD => {
+                    InteractionItem::Leaderboard(LeaderboardPage::first())
+                }
+                consts::interact::GIVE => {
+                    let options = SubcommandOptions::new(cmd, subcommand)?;
+                    let recipient = options.user(consts::interact::USER)?;
+                    InteractionItem::Give(Gift {
+                        recipient,
+                        to_bot: is_bot(recipient),
+                        amount: options.amount(consts::interact::AMOUNT)?,
+                    })
+                }
                 _ => {
                     return Err(anyhow::anyhow!("Unknown Subcommand: {cmd} {name}"));
                 }
             }
         }
-        consts::interact::PIKABOO_MOD | _ => InteractionItem::Unimplemented,
+        cmd @ consts::interact::PIKABOO_MOD => {
+            if !is_moderator(interaction.member.as_ref()) {
+                return Ok(InteractionItem::Forbidden);
+            }
+
+            let Some(subcommand) = data.options.first() else {
+                return Err(anyhow::anyhow!("Subcommand is needed: {cmd}"));
+            };
+            let options = SubcommandOptions::new(cmd, subcommand)?;
+            match subcommand.name.as_str() {
+                consts::interact::RECONCILE => InteractionItem::Reconcile,
+                consts::interact::FILTERS => InteractionItem::FilterStats,
+                consts::interact::LEDGER => {
+                    InteractionItem::Ledger(options.user(consts::interact::USER)?)
+                }
+                consts::interact::CONFIG_VIEW => InteractionItem::ConfigView,
+                consts::interact::CONFIG_SET => InteractionItem::ConfigSet {
+                    key: options.string(consts::interact::KEY)?.to_string(),
+                    value: options.string(consts::interact::VALUE)?.to_string(),
+                },
+                consts::interact::GRANT => InteractionItem::GrantEnergy {
+                    target: options.user(consts::interact::USER)?,
+                    amount: options.amount(consts::interact::AMOUNT)?,
+                },
+                consts::interact::REVOKE => InteractionItem::RevokeEnergy {
+                    target: options.user(consts::interact::USER)?,
+                    amount: options.amount(consts::interact::AMOUNT)?,
+                },
+                consts::interact::ROLE_EXPIRE => {
+                    InteractionItem::ExpireCustomRole(options.user(consts::interact::USER)?)
+                }
+                consts::interact::ROLE_EXTEND => InteractionItem::ExtendCustomRole {
+                    target: options.user(consts::interact::USER)?,
+                    days: options.amount(consts::interact::DAYS)?,
+                },
+                consts::interact::INSPECT => {
+                    InteractionItem::Inspect(options.user(consts::interact::USER)?)
+                }
+                consts::interact::ZONES => InteractionItem::Zones(ZonesAction::View),
+                _ => InteractionItem::Unimplemented,
+            }
+        }
+        _ => InteractionItem::Unimplemented,
     })
 }
 
+/// Whether the member behind the interaction can use the moderator tools
+fn is_moderator(member: Option<&PartialMember>) -> bool {
+    member
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD))
+}
+
+/// Options given to a subcommand, looked up by name
+struct SubcommandOptions<'a> {
+    path: String,
+    options: &'a [CommandDataOption],
+}
+
+impl<'a> SubcommandOptions<'a> {
+    fn new(cmd: &str, subcommand: &'a CommandDataOption) -> Result<SubcommandOptions<'a>> {
+        let path = format!("{cmd} {}", subcommand.name);
+        let CommandOptionValue::SubCommand(options) = &subcommand.value else {
+            return Err(anyhow::anyhow!("Malformed subcommand: {path}"));
+        };
+        Ok(SubcommandOptions { path, options })
+    }
+
+    fn get(&self, name: &str) -> Result<&'a CommandOptionValue> {
+        self.options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| &option.value)
+            .ok_or_else(|| anyhow::anyhow!("Option `{name}` is needed: {}", self.path))
+    }
+
+    fn user(&self, name: &str) -> Result<Id<UserMarker>> {
+        match self.get(name)? {
+            CommandOptionValue::User(user_id) => Ok(*user_id),
+            _ => Err(anyhow::anyhow!(
+                "Option `{name}` must be a user: {}",
+                self.path
+            )),
+        }
+    }
+
+    /// Negative values become zero, which every handler turns down
+    fn amount(&self, name: &str) -> Result<u64> {
+        match self.get(name)? {
+            CommandOptionValue::Integer(value) => Ok(u64::try_from(*value).unwrap_or(0)),
+            _ => Err(anyhow::anyhow!(
+                "Option `{name}` must be an integer: {}",
+                self.path
+            )),
+        }
+    }
+
+    fn string(&self, name: &str) -> Result<&'a str> {
+        match self.get(name)? {
+            CommandOptionValue::String(value) => Ok(value.as_str()),
+            _ => Err(anyhow::anyhow!(
+                "Option `{name}` must be a string: {}",
+                self.path
+            )),
+        }
+    }
+}
+
 fn component_extractor(interaction: Interaction) -> Result<InteractionItem> {
     use twilight_model::channel::message::component::ComponentType;
 
+    let by_moderator = is_moderator(interaction.member.as_ref());
     let Some(InteractionData::MessageComponent(data)) = interaction.data else {
         return Err(anyhow::anyhow!("Component without data"));
     };
+    let Some(custom_id) = decode_custom_id(&data.custom_id)? else {
+        return Ok(InteractionItem::Outdated);
+    };
+
     Ok(match data.component_type {
-        ComponentType::Button => match data.custom_id.as_str() {
-            consts::interact::INVENTORY => InteractionItem::Inventory,
-            consts::interact::SHOP => InteractionItem::Shop,
-            consts::interact::CUSTOMROLE => InteractionItem::CustomRole,
-            consts::interact::CUSTOMROLE_SUBSCRIBE => InteractionItem::CustomRoleSubscribe,
-            consts::interact::CUSTOMROLE_UNSUBSCRIBE => InteractionItem::CustomRoleUnsubscribe,
+        ComponentType::Button => match custom_id {
+            CustomId::Inventory => InteractionItem::Inventory,
+            CustomId::Shop => InteractionItem::Shop,
+            CustomId::Leaderboard => InteractionItem::Leaderboard(LeaderboardPage::first()),
+            CustomId::LeaderboardPage { kind, page } => {
+                InteractionItem::Leaderboard(LeaderboardPage {
+                    kind,
+                    page,
+                    update: true,
+                })
+            }
+            CustomId::ZonesRemove(_) if !by_moderator => InteractionItem::Forbidden,
+            CustomId::ZonesRemove(channel_id) => {
+                InteractionItem::Zones(ZonesAction::Remove(channel_id))
+            }
+            CustomId::CustomRole => InteractionItem::CustomRole,
+            CustomId::CustomRoleSubscribe => InteractionItem::CustomRoleSubscribe,
+            CustomId::CustomRoleUnsubscribe => InteractionItem::CustomRoleUnsubscribe,
+            CustomId::CustomRoleEdit(field) => InteractionItem::CustomRoleEditor(field),
+            CustomId::CustomRoleToggleMentionable => {
+                InteractionItem::ConfirmCustomRoleEdit(CustomRoleEdit::ToggleMentionable)
+            }
             _ => InteractionItem::Unimplemented,
         },
-        ComponentType::TextSelectMenu => match data.custom_id.as_str() {
-            consts::interact::SHOP => match data.values[0].as_str() {
+        ComponentType::TextSelectMenu => match custom_id {
+            CustomId::Shop => match data.values[0].as_str() {
                 consts::interact::SHOP_CUSTOMROLE => InteractionItem::ShopCustomRole,
                 consts::interact::SHOP_NICKNAME => InteractionItem::ShopNickname,
                 consts::interact::SHOP_ENERGYBOOST => InteractionItem::ShopEnergyBoost,
                 consts::interact::SHOP_BLINDBOX => InteractionItem::ShopBlindBox,
                 _ => InteractionItem::Unimplemented,
             },
-            consts::interact::INVENTORY => match data.values[0].as_str() {
+            CustomId::Inventory => match data.values[0].as_str() {
                 consts::interact::INVENTORY_CUSTOMROLE => InteractionItem::InventoryCustomRole,
                 consts::interact::INVENTORY_NICKNAME => InteractionItem::InventoryNickname,
                 _ => InteractionItem::Unimplemented,
             },
             _ => InteractionItem::Unimplemented,
         },
+        ComponentType::ChannelSelectMenu => match custom_id {
+            CustomId::ZonesAdd if !by_moderator => InteractionItem::Forbidden,
+            CustomId::ZonesAdd => {
+                let channels = data
+                    .values
+                    .iter()
+                    .map(|value| value.parse())
+                    .collect::<Result<Vec<_>, _>>()?;
+                InteractionItem::Zones(ZonesAction::Add(channels))
+            }
+            _ => InteractionItem::Unimplemented,
+        },
         ComponentType::ActionRow
         | ComponentType::TextInput
         | ComponentType::UserSelectMenu
         | ComponentType::RoleSelectMenu
         | ComponentType::MentionableSelectMenu
-        | ComponentType::ChannelSelectMenu
         | ComponentType::Unknown(_)
         | _ => InteractionItem::Unimplemented,
     })
@@ -243,6 +756,9 @@ fn modal_extractor(interaction: Interaction) -> Result<InteractionItem> {
     let Some(InteractionData::ModalSubmit(data)) = interaction.data else {
         return Err(anyhow::anyhow!("Modal without data"));
     };
+    let Some(custom_id) = decode_custom_id(&data.custom_id)? else {
+        return Ok(InteractionItem::Outdated);
+    };
 
     let mut inputs = data
         .components
@@ -250,43 +766,46 @@ fn modal_extractor(interaction: Interaction) -> Result<InteractionItem> {
         .filter_map(|mut ar| ar.components.pop())
         .filter_map(|item| Some((item.custom_id, item.value?)))
         .collect::<HashMap<_, _>>();
+    let mut input = |input_id: &str| {
+        inputs.remove(input_id).ok_or_else(|| {
+            anyhow::anyhow!("Modal `{}` expects to have a `{input_id}`", data.custom_id)
+        })
+    };
 
-    Ok(match data.custom_id.as_str() {
-        cid @ (consts::interact::CONFIRM_ORDER_CUSTOMROLE
-        | consts::interact::CONFIRM_ORDER_NICKNAME
-        | consts::interact::CONFIRM_ORDER_ENERGYBOOST
-        | consts::interact::CONFIRM_ORDER_BLINDBOX) => {
-            let confirmation = inputs
-                .remove(consts::interact::CONFIRM_OKAY)
-                .ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Modal `{cid}` expects to have a `{}`",
-                        consts::interact::CONFIRM_OKAY
-                    )
-                })?;
-
-            InteractionItem::ConfirmOrder(ConfirmOrder::new(
-                &confirmation,
-                match cid {
-                    consts::interact::CONFIRM_ORDER_CUSTOMROLE => OrderItem::CustomRole,
-                    consts::interact::CONFIRM_ORDER_NICKNAME => OrderItem::Nickname,
-                    consts::interact::CONFIRM_ORDER_ENERGYBOOST => OrderItem::EnergyBoost,
-                    consts::interact::CONFIRM_ORDER_BLINDBOX => OrderItem::BlindBox,
-                    _ => unreachable!(),
-                },
-            ))
+    Ok(match custom_id {
+        CustomId::ConfirmOrder(item) => InteractionItem::ConfirmOrder(ConfirmOrder::new(
+            &input(consts::interact::CONFIRM_OKAY)?,
+            item,
+        )),
+        CustomId::ChangeNickname => {
+            let nickname = input(consts::interact::NICKNAME)?;
+            InteractionItem::ConfirmInventoryNickname(ConfirmChangeNickname { nickname })
         }
-        cid @ consts::interact::CHANGE_NICKNAME => {
-            let nickname = inputs.remove(consts::interact::NICKNAME).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Modal `{cid}` expects to have a `{}`",
-                    consts::interact::NICKNAME
-                )
-            })?;
-
-            let data = ConfirmChangeNickname { nickname };
-            InteractionItem::ConfirmInventoryNickname(data)
+        CustomId::CustomRoleEdit(field) => InteractionItem::ConfirmCustomRoleEdit(match field {
+            CustomRoleField::Name => CustomRoleEdit::Name(input(consts::interact::ROLE_NAME)?),
+            CustomRoleField::Color => CustomRoleEdit::Color(input(consts::interact::ROLE_COLOR)?),
+            CustomRoleField::Icon => CustomRoleEdit::Icon(input(consts::interact::ROLE_ICON)?),
+        }),
+        CustomId::GiftEnergy(recipient) => {
+            let amount = input(consts::interact::AMOUNT)?;
+
+            // Anything that isn't a number falls below the minimum amount
+            InteractionItem::Give(Gift {
+                recipient,
+                to_bot: false,
+                amount: amount.trim().parse().unwrap_or(0),
+            })
         }
         _ => InteractionItem::Unimplemented,
     })
 }
+
+/// Decodes the `custom_id` of a component or a modal, `None` standing for one sent by an
+/// older version of the bot
+fn decode_custom_id(custom_id: &str) -> Result<Option<CustomId>> {
+    match CustomId::decode(custom_id) {
+        Ok(custom_id) => Ok(Some(custom_id)),
+        Err(DecodeError::Outdated) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -1,18 +1,37 @@
 use anyhow::Result;
+use tracing::info;
 use twilight_model::gateway::payload::incoming::MemberChunk;
 
-use crate::core::app_state::AppState;
+use crate::{consts, core::app_state::AppState};
 
 pub fn handle(state: AppState, member_chunk: MemberChunk) -> Result<()> {
-    let mut boosters = state.cache.boosters.lock().unwrap();
+    if member_chunk.nonce.as_deref() != Some(consts::MEMBER_CHUNK_NONCE) {
+        return Ok(());
+    }
 
-    member_chunk
-        .members
-        .iter()
-        .filter(|mem| mem.premium_since.is_some())
-        .for_each(|mem| {
-            boosters.insert(mem.user.id);
-        });
+    let mut member_chunks = state.cache.member_chunks.lock().unwrap();
+    member_chunks.received += 1;
+    member_chunks.boosters.extend(
+        member_chunk
+            .members
+            .iter()
+            .filter(|mem| mem.premium_since.is_some())
+            .map(|mem| mem.user.id),
+    );
+
+    // Chunks may be handled out of order, so count them instead of trusting the index
+    if member_chunks.received < member_chunk.chunk_count {
+        return Ok(());
+    }
+
+    let boosters = std::mem::take(&mut *member_chunks).boosters;
+    drop(member_chunks);
+
+    info!(
+        count = boosters.len(),
+        "Booster set rebuilt from member chunks"
+    );
+    *state.cache.boosters.lock().unwrap() = boosters;
 
     Ok(())
 }
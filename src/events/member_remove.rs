@@ -0,0 +1,15 @@
+use anyhow::Result;
+use twilight_model::gateway::payload::incoming::MemberRemove;
+
+use crate::core::app_state::AppState;
+
+pub fn handle(state: AppState, member_remove: MemberRemove) -> Result<()> {
+    state
+        .cache
+        .boosters
+        .lock()
+        .unwrap()
+        .remove(&member_remove.user.id);
+
+    Ok(())
+}
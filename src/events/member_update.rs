@@ -1,16 +1,89 @@
 use anyhow::Result;
-use twilight_model::gateway::payload::incoming::MemberUpdate;
+use tracing::warn;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    gateway::payload::incoming::MemberUpdate,
+    id::{Id, marker::UserMarker},
+};
 
 use crate::core::app_state::AppState;
 
-pub fn handle(state: AppState, member_update: Box<MemberUpdate>) -> Result<()> {
+pub async fn handle(state: AppState, member_update: Box<MemberUpdate>) -> Result<()> {
+    let user_id = member_update.user.id;
     let is_booster = member_update.premium_since.is_some();
-    let mut boosters = state.cache.boosters.lock().unwrap();
+    let was_booster = {
+        let mut boosters = state.cache.boosters.lock().unwrap();
 
-    if is_booster {
-        boosters.insert(member_update.user.id);
-    } else {
-        boosters.remove(&member_update.user.id);
+        if is_booster {
+            !boosters.insert(user_id)
+        } else {
+            boosters.remove(&user_id)
+        }
+    };
+
+    match (was_booster, is_booster) {
+        (true, false) => start_grace_period(state, user_id).await,
+        (_, true) => cancel_grace_period(state, user_id).await,
+        (false, false) => Ok(()),
+    }
+}
+
+async fn start_grace_period(state: AppState, user_id: Id<UserMarker>) -> Result<()> {
+    let Some(role) = state.cache.user_custom_roles.get(user_id) else {
+        return Ok(());
+    };
+
+    let (grace_period, fee) = {
+        let config = state.config.read().unwrap();
+        (config.booster_grace_period, config.service_fee.custom_role)
+    };
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let ends_at = now + grace_period;
+
+    state.db.update_booster_grace(user_id, ends_at).await?;
+    state
+        .cache
+        .booster_graces
+        .lock()
+        .unwrap()
+        .insert(user_id, ends_at);
+
+    let ends_at = Timestamp::new(ends_at, Some(TimestampStyle::LongDateTime)).mention();
+    let afterwards = match role.auto_renewal {
+        true => format!(
+            "it renews for {fee} ⚡ each time it expires, as long as your balance covers it"
+        ),
+        false => "it is removed once it expires, since auto-renewal is off".to_string(),
+    };
+    let content = format!(
+        "Thank you for boosting the server! 💖\n\
+        Your boost has ended, so your custom role **{name}** is in a grace period until {ends_at}. \
+        Boost again before then and nothing changes.\n\
+        After the grace period, {afterwards}.",
+        name = role.name,
+    );
+
+    if let Err(err) = state.direct_message(user_id, &content).await {
+        warn!(?err, "Unable to tell <{user_id}> about their grace period");
+    }
+
+    Ok(())
+}
+
+async fn cancel_grace_period(state: AppState, user_id: Id<UserMarker>) -> Result<()> {
+    let in_grace = state
+        .cache
+        .booster_graces
+        .lock()
+        .unwrap()
+        .remove(&user_id)
+        .is_some();
+
+    if in_grace {
+        state.db.delete_booster_grace(user_id).await?;
     }
 
     Ok(())
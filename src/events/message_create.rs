@@ -1,26 +1,52 @@
 use anyhow::Result;
+use tracing::debug;
 use twilight_model::gateway::payload::incoming::MessageCreate;
 use twilight_util::snowflake::Snowflake;
 
-use crate::core::app_state::AppState;
+use crate::core::{app_state::AppState, cache::CheckinMessage, database::LedgerReason};
 
 pub async fn handle(state: AppState, msg: Box<MessageCreate>) -> Result<()> {
     let timestamp = msg.id.timestamp() as u64 / 1000;
     let user_id = msg.author.id;
     let channel_id = msg.channel_id;
-    let in_the_zone = state.config.read().unwrap().zones.contains(&channel_id);
+    let zone = state.config.read().unwrap().zones.get(&channel_id).copied();
 
-    if !in_the_zone {
+    let Some(zone) = zone else {
+        return Ok(());
+    };
+
+    if let Err(rejection) = state.message_filter.check(&msg) {
+        debug!(?rejection, "Message {} doesn't count as a check-in", msg.id);
         return Ok(());
     }
 
-    let new_checkin = state.checkin_note.checkin(user_id, timestamp);
+    let new_checkin = state.checkin_note.checkin(user_id, channel_id, timestamp);
 
     if !new_checkin {
         return Ok(());
     }
 
-    state.cache.energy_balance.add_one(user_id).await?;
+    let amount = zone.energy * state.cache.energy_boosts.multiplier(user_id, timestamp);
+    state
+        .cache
+        .energy_balance
+        .add_energy(user_id, amount, LedgerReason::Checkin, Some(msg.id.get()))
+        .await?;
+
+    // Remember the message for a while, deleting it right away gives the energy back
+    let clawback_window = state.config.read().unwrap().clawback_window;
+    if clawback_window > 0 {
+        let mut checkin_messages = state.cache.checkin_messages.lock().unwrap();
+        checkin_messages.retain(|_, checkin| checkin.earned_at + clawback_window >= timestamp);
+        checkin_messages.insert(
+            msg.id,
+            CheckinMessage {
+                user_id,
+                energy: amount,
+                earned_at: timestamp,
+            },
+        );
+    }
 
     Ok(())
 }
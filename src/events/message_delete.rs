@@ -0,0 +1,42 @@
+use anyhow::Result;
+use tracing::info;
+use twilight_model::id::{Id, marker::MessageMarker};
+
+use crate::core::{app_state::AppState, database::LedgerReason};
+
+/// Takes back the energy earned by messages deleted within the clawback window.
+pub async fn handle(state: AppState, message_ids: &[Id<MessageMarker>]) -> Result<()> {
+    let clawback_window = state.config.read().unwrap().clawback_window;
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+    let checkins = {
+        let mut checkin_messages = state.cache.checkin_messages.lock().unwrap();
+        message_ids
+            .iter()
+            .filter_map(|message_id| {
+                let checkin = checkin_messages.remove(message_id)?;
+                Some((*message_id, checkin))
+            })
+            .filter(|(_, checkin)| checkin.earned_at + clawback_window >= now)
+            .collect::<Vec<_>>()
+    };
+
+    for (message_id, checkin) in checkins {
+        let taken = state
+            .cache
+            .energy_balance
+            .take_energy(
+                checkin.user_id,
+                checkin.energy,
+                LedgerReason::Clawback,
+                Some(message_id.get()),
+            )
+            .await?;
+        info!(
+            "Clawed back {taken} energy from <{}> for deleting message {message_id}",
+            checkin.user_id
+        );
+    }
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 mod guild_create;
 pub mod interaction_create;
 mod member_chunk;
+mod member_remove;
 mod member_update;
 mod message_create;
+mod message_delete;
 mod ready;
+mod role_delete;
 mod role_update;
 
 use anyhow::Result;
@@ -17,11 +20,15 @@ pub async fn event_handler(state: AppState, event: Event) -> Result<()> {
             interaction_create::handle(state, interaction).await
         }
         Event::MessageCreate(msg) => message_create::handle(state, msg).await,
-        Event::MemberUpdate(member_update) => member_update::handle(state, member_update),
+        Event::MessageDelete(msg) => message_delete::handle(state, &[msg.id]).await,
+        Event::MessageDeleteBulk(msgs) => message_delete::handle(state, &msgs.ids).await,
+        Event::MemberUpdate(member_update) => member_update::handle(state, member_update).await,
         Event::RoleUpdate(role_update) => role_update::handle(state, role_update),
-        Event::GuildCreate(guild_create) => guild_create::handle(state, guild_create),
-        // Event::MemberChunk(member_chunk) => member_chunk::handle(state, member_chunk),
-        Event::Ready(ready) => ready::handle(ready),
+        Event::RoleDelete(role_delete) => role_delete::handle(state, role_delete).await,
+        Event::GuildCreate(guild_create) => guild_create::handle(state, guild_create).await,
+        Event::MemberChunk(member_chunk) => member_chunk::handle(state, member_chunk),
+        Event::MemberRemove(member_remove) => member_remove::handle(state, member_remove),
+        Event::Ready(ready) => ready::handle(state, ready).await,
         _ => Ok(()), // Ignore other events
     }
 }
@@ -1,8 +1,22 @@
 use anyhow::Result;
-use tracing::info;
-use twilight_model::gateway::payload::incoming::Ready;
+use tracing::{error, info};
+use twilight_model::gateway::payload::{incoming::Ready, outgoing::RequestGuildMembers};
 
-pub fn handle(ready: Box<Ready>) -> Result<()> {
+use crate::{commands, consts, core::app_state::AppState};
+
+pub async fn handle(state: AppState, ready: Box<Ready>) -> Result<()> {
     info!("{} is ready!", ready.user.name);
+
+    // Collect every member to rebuild the booster set from scratch
+    *state.cache.member_chunks.lock().unwrap() = Default::default();
+    let request = RequestGuildMembers::builder(state.config.env.guild_id)
+        .nonce(consts::MEMBER_CHUNK_NONCE)
+        .query("", None);
+    state.gateway.command(&request)?;
+
+    if let Err(err) = commands::sync(&state, ready.application.id).await {
+        error!(?err, "Unable to register commands");
+    }
+
     Ok(())
 }
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use tracing::{info, warn};
+use twilight_model::gateway::payload::incoming::RoleDelete;
+
+use crate::core::app_state::AppState;
+
+pub async fn handle(state: AppState, role_delete: RoleDelete) -> Result<()> {
+    let role_id = role_delete.role_id;
+
+    // Roles removed by the bot itself are already gone from the cache
+    let role = {
+        let mut user_custom_roles = state.cache.user_custom_roles.lock().unwrap();
+        user_custom_roles
+            .iter()
+            .find(|(_, crole)| crole.role_id == role_id)
+            .map(|(&user_id, _)| user_id)
+            .and_then(|user_id| user_custom_roles.remove(&user_id))
+    };
+
+    let deleted = state.db.delete_custom_role_by_role_id(role_id).await?;
+    let Some(role) = role else {
+        return Ok(());
+    };
+    info!(
+        deleted,
+        "Custom role <{role_id}> of <{}> was deleted", role.user_id
+    );
+
+    let notify = state.config.read().unwrap().notify_role_delete;
+    if !notify {
+        return Ok(());
+    }
+
+    let content = format!(
+        "Your custom role **{}** was deleted from the server by a moderator, \
+        so it has been removed from your inventory.",
+        role.name
+    );
+    if let Err(err) = state.direct_message(role.user_id, &content).await {
+        warn!(
+            ?err,
+            "Unable to tell <{}> about their deleted role", role.user_id
+        );
+    }
+
+    Ok(())
+}
@@ -4,5 +4,15 @@ use twilight_model::gateway::payload::incoming::RoleUpdate;
 use crate::core::app_state::AppState;
 
 pub fn handle(state: AppState, role_update: RoleUpdate) -> Result<()> {
+    let RoleUpdate { role, .. } = role_update;
+    let mut user_custom_roles = state.cache.user_custom_roles.lock().unwrap();
+
+    if let Some(crole) = user_custom_roles
+        .values_mut()
+        .find(|crole| crole.role_id == role.id)
+    {
+        crole.apply_role(role);
+    }
+
     Ok(())
 }
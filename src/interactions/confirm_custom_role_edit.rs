@@ -0,0 +1,166 @@
+use anyhow::Result;
+use twilight_http::error::ErrorType;
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+use unicode_properties::UnicodeEmoji;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    core::app_state::AppState,
+    events::interaction_create::CustomRoleEdit,
+    interactions::{
+        custom_role::{self, lack_of_custom_role},
+        custom_role_editor::{
+            MAX_ROLE_NAME_LENGTH, rate_limited, role_icons_unsupported, supports_role_icons,
+        },
+        ephemeral_response,
+    },
+};
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    edit: CustomRoleEdit,
+) -> Result<InteractionResponse> {
+    let Some(mut role) = state.cache.user_custom_roles.get(user_id) else {
+        return lack_of_custom_role();
+    };
+
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    if let Some(response) = rate_limited(&state, role.role_id, now) {
+        return Ok(response);
+    }
+
+    let request = state
+        .app
+        .update_role(state.config.env.guild_id, role.role_id);
+    let rs = match edit {
+        CustomRoleEdit::Name(name) => {
+            let name = name.trim();
+            let length = name.chars().count();
+            if length == 0 || length > MAX_ROLE_NAME_LENGTH as usize {
+                return Ok(ephemeral_response(format!(
+                    "Role names must be between 1 and {MAX_ROLE_NAME_LENGTH} characters long."
+                )));
+            }
+            request.name(Some(name)).await
+        }
+        CustomRoleEdit::Color(color) => {
+            let Some(color) = parse_color(&color) else {
+                return Ok(ephemeral_response(format!(
+                    "`{}` is not a color I know. Try a hex code like `#ffbe98`, \
+                    `rgb(255, 190, 152)` or a CSS name like `peachpuff`.",
+                    color.trim()
+                )));
+            };
+            request.color(Some(color)).await
+        }
+        CustomRoleEdit::Icon(emoji) => {
+            let emoji = emoji.trim();
+            if !is_single_emoji(emoji) {
+                return Ok(ephemeral_response(
+                    "Role icons must be a single unicode emoji, like 🌸.",
+                ));
+            }
+            if !supports_role_icons(&state).await? {
+                return Ok(role_icons_unsupported());
+            }
+            request.icon(None).unicode_emoji(Some(emoji)).await
+        }
+        CustomRoleEdit::ToggleMentionable => request.mentionable(!role.mentionable).await,
+    };
+
+    let updated = match rs {
+        Ok(response) => response.model().await?,
+        Err(err) => {
+            return match err.kind() {
+                ErrorType::Response { status, .. } if status.get() == 400 => Ok(
+                    ephemeral_response("Discord rejected that change, please try something else."),
+                ),
+                ErrorType::Response { status, .. } if status.get() == 403 => Ok(
+                    ephemeral_response("I don't have permission to edit your role anymore."),
+                ),
+                _ => Err(err.into()),
+            };
+        }
+    };
+
+    role.apply_role(updated);
+    state.cache.user_custom_roles.update(role.clone());
+    state
+        .cache
+        .role_edits
+        .lock()
+        .unwrap()
+        .insert(role.role_id, now);
+
+    custom_role::run(state, user_id)
+}
+
+/// Parses a color written as hex (`#ffbe98`, `ffbe98`, `0xffbe98`), `rgb(r, g, b)` or a
+/// CSS color name.
+fn parse_color(input: &str) -> Option<u32> {
+    let input = input.trim();
+    let is_bare_hex = matches!(input.len(), 3 | 6) && input.chars().all(|c| c.is_ascii_hexdigit());
+    let input = match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => format!("#{hex}"),
+        None if is_bare_hex => format!("#{input}"),
+        None => input.to_string(),
+    };
+
+    let [r, g, b, _] = csscolorparser::parse(&input).ok()?.to_rgba8();
+    Some(u32::from_be_bytes([0, r, g, b]))
+}
+
+/// Whether the input is exactly one unicode emoji, sequences like flags, keycaps or skin
+/// tones included.
+fn is_single_emoji(input: &str) -> bool {
+    let mut graphemes = input.graphemes(true);
+    let (Some(emoji), None) = (graphemes.next(), graphemes.next()) else {
+        return false;
+    };
+    let Some(first) = emoji.chars().next() else {
+        return false;
+    };
+
+    // Digits, `#` and `*` are emoji characters, but only show as one in a keycap like 1️⃣
+    first.is_emoji_char() && (!first.is_ascii() || emoji.ends_with('\u{20E3}'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_emojis_are_accepted() {
+        for emoji in ["🌸", "1️⃣", "#️⃣", "❤️", "👍🏽", "🇻🇳", "👩‍💻", "🏳️‍🌈"]
+        {
+            assert!(is_single_emoji(emoji), "{emoji}");
+        }
+    }
+
+    #[test]
+    fn everything_else_is_rejected() {
+        for input in [
+            "",
+            " ",
+            "a",
+            "1",
+            "#",
+            "é",
+            "日",
+            "日本",
+            "🌸🌸",
+            "🌸 ",
+            "a⃣",
+            "<:wave:123>",
+        ] {
+            assert!(!is_single_emoji(input), "{input:?}");
+        }
+    }
+}
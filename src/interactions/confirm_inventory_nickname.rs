@@ -1,15 +1,81 @@
 use anyhow::Result;
+use tracing::error;
+use twilight_http::error::ErrorType;
 use twilight_model::{
     http::interaction::InteractionResponse,
     id::{Id, marker::UserMarker},
 };
 
-use crate::{core::app_state::AppState, events::interaction_create::ConfirmChangeNickname};
+use crate::{
+    core::{app_state::AppState, database::InventoryItem},
+    events::interaction_create::ConfirmChangeNickname,
+    interactions::{
+        ephemeral_response,
+        inventory_nickname::{MAX_NICKNAME_LENGTH, lack_of_nickname_token},
+    },
+};
 
 pub async fn run(
     state: AppState,
     data: ConfirmChangeNickname,
     user_id: Id<UserMarker>,
 ) -> Result<InteractionResponse> {
-    todo!()
+    let nickname = data.nickname.trim();
+    let length = nickname.chars().count();
+
+    if length == 0 || length > MAX_NICKNAME_LENGTH as usize {
+        return Ok(ephemeral_response(format!(
+            "Nicknames must be between 1 and {MAX_NICKNAME_LENGTH} characters long."
+        )));
+    }
+
+    let consumed = state
+        .db
+        .consume_inventory_item(user_id, InventoryItem::Nickname)
+        .await?;
+
+    if !consumed {
+        return Ok(lack_of_nickname_token());
+    }
+
+    let rs = state
+        .app
+        .update_guild_member(state.config.env.guild_id, user_id)
+        .nick(Some(nickname))
+        .await;
+
+    let Err(err) = rs else {
+        return Ok(ephemeral_response(format!(
+            "📝 Your nickname is now \"{nickname}\"!"
+        )));
+    };
+
+    // The nickname was not changed, hand the token back
+    if let Err(refund_err) = state
+        .db
+        .add_inventory_item(user_id, InventoryItem::Nickname, 1)
+        .await
+    {
+        error!(
+            ?refund_err,
+            "Unable to give back nickname change to <{user_id}>"
+        );
+    }
+
+    match err.kind() {
+        ErrorType::Response { status, .. } if status.get() == 403 => Ok(ephemeral_response(
+            "I don't have permission to change your nickname. \
+            Server owners and members above my role can't be renamed by bots. \
+            Your nickname change was not used.",
+        )),
+        ErrorType::Response { status, .. } if status.get() == 400 => Ok(ephemeral_response(
+            "Discord rejected that nickname, please try a different one. \
+            Your nickname change was not used.",
+        )),
+        ErrorType::Validation => Ok(ephemeral_response(format!(
+            "Nicknames must be between 1 and {MAX_NICKNAME_LENGTH} characters long. \
+            Your nickname change was not used."
+        ))),
+        _ => Err(err.into()),
+    }
 }
@@ -1,8 +1,74 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use twilight_model::{
+    channel::message::{
+        Component,
+        component::{ActionRow, TextInput, TextInputStyle},
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::UserMarker},
+};
 
-use crate::events::interaction_create::ConfirmOrder;
+use crate::{
+    consts,
+    core::app_state::AppState,
+    custom_id::CustomId,
+    events::interaction_create::{ConfirmOrder, Defer, OrderItem},
+    interactions::{
+        ephemeral_response, shop_blind_box, shop_custom_role, shop_energy_boost, shop_nickname,
+    },
+};
 
-pub fn run(data: ConfirmOrder) -> Result<InteractionResponse> {
-    todo!()
+pub async fn run(
+    state: AppState,
+    data: ConfirmOrder,
+    user_id: Id<UserMarker>,
+    defer: &Defer,
+) -> Result<InteractionResponse> {
+    if !data.okay {
+        return Ok(ephemeral_response("Order cancelled, no energy was spent."));
+    }
+
+    // Ownership checks and payment of an order must not interleave with another one
+    let Some(_order) = state.cache.pending_orders.start(user_id) else {
+        return Ok(ephemeral_response(
+            "Your previous order is still being processed, please wait a moment.",
+        ));
+    };
+
+    // Payments go through Discord and the database, the outcome is only shown to the buyer
+    defer.message(true);
+    match data.item {
+        OrderItem::CustomRole => shop_custom_role::purchase(state.clone(), user_id).await,
+        OrderItem::Nickname => shop_nickname::purchase(state.clone(), user_id).await,
+        OrderItem::EnergyBoost => shop_energy_boost::purchase(state.clone(), user_id).await,
+        OrderItem::BlindBox => shop_blind_box::purchase(state.clone(), user_id).await,
+    }
+}
+
+/// Builds the modal asking the user to confirm an order of `item` for `fee` energy.
+///
+/// The submission is parsed back into a [`ConfirmOrder`] by the modal extractor.
+pub fn modal(order: OrderItem, item: &str, fee: u64) -> Result<InteractionResponse> {
+    let text_input = Component::TextInput(TextInput {
+        custom_id: consts::interact::CONFIRM_OKAY.to_string(),
+        label: format!("Type \"okay\" to pay {fee} ⚡"),
+        max_length: Some(10),
+        min_length: None,
+        placeholder: Some("okay".to_string()),
+        required: Some(true),
+        style: TextInputStyle::Short,
+        value: None,
+    });
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(InteractionResponseData {
+            custom_id: Some(CustomId::ConfirmOrder(order).encode()?),
+            title: Some(format!("Buy {item}")),
+            components: Some(vec![Component::ActionRow(ActionRow {
+                components: vec![text_input],
+            })]),
+            ..Default::default()
+        }),
+    })
 }
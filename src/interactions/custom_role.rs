@@ -1,12 +1,20 @@
 use anyhow::Result;
 use twilight_model::{
-    channel::message::MessageFlags,
+    channel::message::{
+        Component, EmojiReactionType, MessageFlags,
+        component::{ActionRow, Button, ButtonStyle},
+    },
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
     id::{Id, marker::UserMarker},
 };
 use twilight_util::builder::embed::{EmbedBuilder, ImageSource};
 
-use crate::core::{app_state::AppState, database::RoleIcon};
+use crate::{
+    core::{app_state::AppState, database::RoleIcon},
+    custom_id::CustomId,
+    events::interaction_create::CustomRoleField,
+    interactions::ephemeral_response,
+};
 
 pub fn run(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
     let role = state
@@ -37,10 +45,59 @@ pub fn run(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionRespon
     }
     let embed = embed_builder.build();
 
+    // Offer the opposite of the current auto-renewal state
+    let (label, custom_id, style, emoji) = match role.auto_renewal {
+        true => (
+            "Disable auto-renewal",
+            CustomId::CustomRoleUnsubscribe,
+            ButtonStyle::Secondary,
+            '⏹',
+        ),
+        false => (
+            "Enable auto-renewal",
+            CustomId::CustomRoleSubscribe,
+            ButtonStyle::Success,
+            '🔁',
+        ),
+    };
+    let subscription = [(label, custom_id, style, emoji)];
+
+    #[rustfmt::skip]
+    const EDIT_ITEMS: [(&str, CustomId, ButtonStyle, char); 4] = [
+        ("Name", CustomId::CustomRoleEdit(CustomRoleField::Name), ButtonStyle::Primary, '✏'),
+        ("Color", CustomId::CustomRoleEdit(CustomRoleField::Color), ButtonStyle::Primary, '🎨'),
+        ("Icon", CustomId::CustomRoleEdit(CustomRoleField::Icon), ButtonStyle::Primary, '🌸'),
+        ("Toggle mention", CustomId::CustomRoleToggleMentionable, ButtonStyle::Secondary, '🔔'),
+    ];
+
+    let components = [&EDIT_ITEMS[..], &subscription[..]]
+        .into_iter()
+        .map(|items| {
+            let components = items
+                .iter()
+                .map(|&(label, custom_id, style, emoji)| {
+                    Ok(Component::Button(Button {
+                        custom_id: Some(custom_id.encode()?),
+                        disabled: false,
+                        emoji: Some(EmojiReactionType::Unicode {
+                            name: emoji.to_string(),
+                        }),
+                        label: Some(label.to_string()),
+                        style,
+                        url: None,
+                        sku_id: None,
+                    }))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Component::ActionRow(ActionRow { components }))
+        })
+        .collect::<Result<_>>()?;
+
     Ok(InteractionResponse {
         kind: InteractionResponseType::ChannelMessageWithSource,
         data: Some(InteractionResponseData {
             embeds: Some(vec![embed]),
+            components: Some(components),
             flags: Some(MessageFlags::EPHEMERAL),
             ..Default::default()
         }),
@@ -48,5 +105,7 @@ pub fn run(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionRespon
 }
 
 pub fn lack_of_custom_role() -> Result<InteractionResponse> {
-    todo!()
+    Ok(ephemeral_response(
+        "You don't own a custom role yet. Grab one from the shop!",
+    ))
 }
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    channel::message::{
+        Component,
+        component::{ActionRow, TextInput, TextInputStyle},
+    },
+    guild::GuildFeature,
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{
+        Id,
+        marker::{RoleMarker, UserMarker},
+    },
+};
+
+use crate::{
+    consts,
+    core::{app_state::AppState, database::RoleIcon},
+    custom_id::CustomId,
+    events::interaction_create::{CustomRoleField, Defer},
+    interactions::{custom_role::lack_of_custom_role, ephemeral_response},
+};
+
+pub const MAX_ROLE_NAME_LENGTH: u16 = 100;
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    field: CustomRoleField,
+    defer: &Defer,
+) -> Result<InteractionResponse> {
+    // Answers with a modal, which has to be the first response
+    defer.forbid();
+
+    let Some(role) = state.cache.user_custom_roles.get(user_id) else {
+        return lack_of_custom_role();
+    };
+
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    if let Some(response) = rate_limited(&state, role.role_id, now) {
+        return Ok(response);
+    }
+
+    let (title, text_input) = match field {
+        CustomRoleField::Name => (
+            "Edit Role Name",
+            TextInput {
+                custom_id: consts::interact::ROLE_NAME.to_string(),
+                label: "Role name".to_string(),
+                max_length: Some(MAX_ROLE_NAME_LENGTH),
+                min_length: Some(1),
+                placeholder: None,
+                required: Some(true),
+                style: TextInputStyle::Short,
+                value: Some(role.name),
+            },
+        ),
+        CustomRoleField::Color => (
+            "Edit Role Color",
+            TextInput {
+                custom_id: consts::interact::ROLE_COLOR.to_string(),
+                label: "Color as hex, rgb(r, g, b) or a CSS name".to_string(),
+                max_length: Some(32),
+                min_length: Some(1),
+                placeholder: Some("#ffbe98, rgb(255, 190, 152) or peachpuff".to_string()),
+                required: Some(true),
+                style: TextInputStyle::Short,
+                value: Some(format!("#{:06X}", role.color)),
+            },
+        ),
+        CustomRoleField::Icon => {
+            if !supports_role_icons(&state).await? {
+                return Ok(role_icons_unsupported());
+            }
+
+            let value = match role.icon {
+                RoleIcon::Unicode(emoji) => Some(emoji),
+                RoleIcon::Custom(_) | RoleIcon::None => None,
+            };
+            (
+                "Edit Role Icon",
+                TextInput {
+                    custom_id: consts::interact::ROLE_ICON.to_string(),
+                    label: "Icon as a single unicode emoji".to_string(),
+                    max_length: Some(32),
+                    min_length: Some(1),
+                    placeholder: Some("🌸".to_string()),
+                    required: Some(true),
+                    style: TextInputStyle::Short,
+                    value,
+                },
+            )
+        }
+    };
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(InteractionResponseData {
+            custom_id: Some(CustomId::CustomRoleEdit(field).encode()?),
+            title: Some(title.to_string()),
+            components: Some(vec![Component::ActionRow(ActionRow {
+                components: vec![Component::TextInput(text_input)],
+            })]),
+            ..Default::default()
+        }),
+    })
+}
+
+/// Replies with the time the role can be edited again if it was edited too recently.
+pub fn rate_limited(
+    state: &AppState,
+    role_id: Id<RoleMarker>,
+    now: u64,
+) -> Option<InteractionResponse> {
+    let cooldown = state.config.read().unwrap().role_edit_cooldown;
+    let last_edit = state
+        .cache
+        .role_edits
+        .lock()
+        .unwrap()
+        .get(&role_id)
+        .copied()?;
+    let available_at = last_edit + cooldown;
+
+    (available_at > now).then(|| {
+        let available_at = Timestamp::new(available_at, Some(TimestampStyle::RelativeTime));
+        ephemeral_response(format!(
+            "Your role was edited recently, you can edit it again {}.",
+            available_at.mention()
+        ))
+    })
+}
+
+/// Role icons need the guild to reach boost level 2.
+pub async fn supports_role_icons(state: &AppState) -> Result<bool> {
+    let guild = state
+        .app
+        .guild(state.config.env.guild_id)
+        .await?
+        .model()
+        .await?;
+
+    Ok(u8::from(guild.premium_tier) >= 2 || guild.features.contains(&GuildFeature::RoleIcons))
+}
+
+pub fn role_icons_unsupported() -> InteractionResponse {
+    ephemeral_response(
+        "Role icons are unlocked at server boost level 2, this server isn't there yet.",
+    )
+}
@@ -1,8 +1,49 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
 
-use crate::core::app_state::AppState;
+use crate::{
+    core::app_state::AppState,
+    interactions::{custom_role::lack_of_custom_role, ephemeral_response},
+};
 
-pub async fn run(state: AppState, status: bool) -> Result<InteractionResponse> {
-    todo!()
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    status: bool,
+) -> Result<InteractionResponse> {
+    let Some(mut role) = state.cache.user_custom_roles.get(user_id) else {
+        return lack_of_custom_role();
+    };
+
+    role.auto_renewal = status;
+    state.db.update_custom_role(&role).await?;
+    state.cache.user_custom_roles.update(role.clone());
+
+    let fee = state.config.read().unwrap().service_fee.custom_role;
+    let mention = role.role_id.mention();
+    let Some(expires_at) = role.expires_at else {
+        return Ok(ephemeral_response(format!(
+            "Auto-renewal is now **{}**. {mention} never expires, so there is nothing to renew.",
+            if status { "on" } else { "off" },
+        )));
+    };
+    let expires_at = Timestamp::new(expires_at, Some(TimestampStyle::ShortDate)).mention();
+
+    let content = match status {
+        true => format!(
+            "🔁 Auto-renewal is now **on**. {mention} will renew on {expires_at} for {fee} ⚡."
+        ),
+        false => format!(
+            "Auto-renewal is now **off**. {mention} will be removed on {expires_at}. \
+            Renewing would cost {fee} ⚡."
+        ),
+    };
+    Ok(ephemeral_response(content))
 }
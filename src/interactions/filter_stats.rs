@@ -0,0 +1,59 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use twilight_model::http::interaction::InteractionResponse;
+
+use crate::{
+    core::{app_state::AppState, message_filter::Rejection},
+    interactions::ephemeral_response,
+};
+
+pub fn run(state: AppState) -> Result<InteractionResponse> {
+    let rules = state.config.read().unwrap().message_filter;
+    let stats = state.message_filter.stats();
+
+    let toggle = |enabled: bool| if enabled { "on" } else { "off" };
+    let threshold = |value: u64| match value {
+        0 => "off".to_string(),
+        value => value.to_string(),
+    };
+
+    let mut content = "# Check-in Filters\n".to_string();
+    let _ = writeln!(&mut content, "**Passed:** {}", stats.passed);
+
+    for rejection in Rejection::ALL {
+        let (key, setting) = match rejection {
+            Rejection::Bot => ("filter_bots", toggle(rules.ignore_bots).to_string()),
+            Rejection::Webhook => ("filter_webhooks", toggle(rules.ignore_webhooks).to_string()),
+            Rejection::System => ("filter_system", toggle(rules.ignore_system).to_string()),
+            Rejection::StickersOrEmojis => (
+                "filter_stickers_emojis",
+                toggle(rules.reject_stickers_emojis).to_string(),
+            ),
+            Rejection::TooShort => ("filter_min_length", threshold(rules.min_length)),
+            Rejection::TooFewWords => ("filter_min_words", threshold(rules.min_words)),
+            Rejection::Duplicate => (
+                "filter_duplicates",
+                toggle(rules.reject_duplicates).to_string(),
+            ),
+        };
+        let count = stats
+            .rejections
+            .get(&rejection)
+            .copied()
+            .unwrap_or_default();
+        let _ = writeln!(
+            &mut content,
+            "- {rejection}: **{count}** rejected (`{key}` = {setting})"
+        );
+    }
+    if !state.config.env.content_filters {
+        content.push_str(
+            "Content rules are off, they need `CONTENT_FILTERS=true` and the message content \
+            intent enabled in the developer portal.\n",
+        );
+    }
+    content.push_str("-# Counted since the last restart");
+
+    Ok(ephemeral_response(content))
+}
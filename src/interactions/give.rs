@@ -0,0 +1,137 @@
+use anyhow::Result;
+use tracing::warn;
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::message::{
+        Component,
+        component::{ActionRow, TextInput, TextInputStyle},
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    consts::{self, DAY_IN_SEC},
+    core::app_state::AppState,
+    custom_id::CustomId,
+    events::interaction_create::Gift,
+    interactions::ephemeral_response,
+};
+
+/// Asks for the amount to gift from the user context menu.
+pub fn modal(
+    state: AppState,
+    recipient: Id<UserMarker>,
+    to_bot: bool,
+) -> Result<InteractionResponse> {
+    if to_bot {
+        return Ok(bot_recipient());
+    }
+
+    let min_amount = state.config.read().unwrap().transfer.min_amount;
+    let text_input = Component::TextInput(TextInput {
+        custom_id: consts::interact::AMOUNT.to_string(),
+        label: "Amount".to_string(),
+        max_length: Some(20),
+        min_length: Some(1),
+        placeholder: Some(format!("At least {min_amount} ⚡")),
+        required: Some(true),
+        style: TextInputStyle::Short,
+        value: None,
+    });
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(InteractionResponseData {
+            custom_id: Some(CustomId::GiftEnergy(recipient).encode()?),
+            title: Some("Gift Energy".to_string()),
+            components: Some(vec![Component::ActionRow(ActionRow {
+                components: vec![text_input],
+            })]),
+            ..Default::default()
+        }),
+    })
+}
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    gift: Gift,
+) -> Result<InteractionResponse> {
+    let Gift {
+        recipient,
+        to_bot,
+        amount,
+    } = gift;
+    let transfer = state.config.read().unwrap().transfer;
+
+    if to_bot {
+        return Ok(bot_recipient());
+    }
+    if recipient == user_id {
+        return Ok(ephemeral_response("You can't gift energy to yourself."));
+    }
+    if amount < transfer.min_amount {
+        return Ok(ephemeral_response(format!(
+            "You need to give at least {} ⚡.",
+            transfer.min_amount
+        )));
+    }
+
+    // The limit is checked against settled gifts, so the sender's gifts go one at a time
+    let Some(_gift) = state.cache.pending_gifts.start(user_id) else {
+        return Ok(ephemeral_response(
+            "Your previous gift is still being processed, please wait a moment.",
+        ));
+    };
+
+    if transfer.daily_limit > 0 {
+        // Pending transfers have to be in the ledger to be counted
+        state.cache.energy_balance.sync_energy_data().await?;
+        let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let given = state
+            .db
+            .fetch_transferred_since(user_id, now.saturating_sub(DAY_IN_SEC))
+            .await?;
+        let left = transfer.daily_limit.saturating_sub(given);
+
+        if amount > left {
+            return Ok(ephemeral_response(format!(
+                "You can only give {left} ⚡ more within 24 hours, the limit is {} ⚡.",
+                transfer.daily_limit
+            )));
+        }
+    }
+
+    let tax = transfer.tax_of(amount);
+    let done = state
+        .cache
+        .energy_balance
+        .transfer(user_id, recipient, amount, tax)
+        .await?;
+
+    if !done {
+        return Ok(ephemeral_response(format!(
+            "You need at least {amount} ⚡ to give that much."
+        )));
+    }
+
+    let received = amount - tax;
+    let notification = format!("🎁 {} gifted you {received} ⚡!", user_id.mention());
+    if let Err(err) = state.direct_message(recipient, &notification).await {
+        warn!(?err, "Unable to notify <{recipient}> about their gift");
+    }
+
+    let tax_note = match tax {
+        0 => String::new(),
+        tax => format!("\n{tax} ⚡ went to the community pot as transfer tax."),
+    };
+    Ok(ephemeral_response(format!(
+        "You gave {received} ⚡ to {}.{tax_note}",
+        recipient.mention()
+    )))
+}
+
+fn bot_recipient() -> InteractionResponse {
+    ephemeral_response("Bots don't need energy, gift it to a member instead!")
+}
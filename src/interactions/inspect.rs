@@ -0,0 +1,92 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    core::{app_state::AppState, database::InventoryItem},
+    interactions::ephemeral_response,
+};
+
+pub async fn run(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    target: Id<UserMarker>,
+) -> Result<InteractionResponse> {
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let timestamp = |secs| Timestamp::new(secs, Some(TimestampStyle::ShortDateTime)).mention();
+
+    let mut content = format!("# Economy of {}\n", target.mention());
+
+    let energy = state.cache.energy_balance.get(target).await?;
+    let _ = writeln!(&mut content, "**Energy:** {energy} ⚡");
+
+    match state
+        .cache
+        .energy_boosts
+        .get(target)
+        .filter(|boost| boost.is_active(now))
+    {
+        Some(boost) => {
+            let _ = writeln!(
+                &mut content,
+                "**Energy Boost:** x{} until {}",
+                boost.multiplier,
+                timestamp(boost.expires_at)
+            );
+        }
+        None => content.push_str("**Energy Boost:** none\n"),
+    }
+
+    match state.cache.user_custom_roles.get(target) {
+        Some(role) => {
+            let expiry = match role.expires_at {
+                Some(expires_at) => format!("expires {}", timestamp(expires_at)),
+                None => "never expires".to_string(),
+            };
+            let _ = writeln!(
+                &mut content,
+                "**Custom Role:** {} ({expiry}, auto-renewal {})",
+                role.role_id.mention(),
+                if role.auto_renewal { "on" } else { "off" }
+            );
+        }
+        None => content.push_str("**Custom Role:** none\n"),
+    }
+
+    let nickname_tokens = state
+        .db
+        .fetch_inventory_item(target, InventoryItem::Nickname)
+        .await?;
+    let _ = writeln!(&mut content, "**Nickname Change:** {nickname_tokens} 📝");
+
+    let is_booster = state.cache.boosters.lock().unwrap().contains(&target);
+    let grace = state
+        .cache
+        .booster_graces
+        .lock()
+        .unwrap()
+        .get(&target)
+        .copied();
+    if is_booster {
+        content.push_str("**Server Booster:** yes\n");
+    } else if let Some(ends_at) = grace {
+        let _ = writeln!(
+            &mut content,
+            "**Server Booster:** grace period until {}",
+            timestamp(ends_at)
+        );
+    }
+
+    state
+        .moderation_log(moderator_id, &format!("inspected {}", target.mention()))
+        .await;
+    Ok(ephemeral_response(content))
+}
@@ -1,7 +1,10 @@
 use std::{fmt::Write, ops::Not};
 
 use anyhow::Result;
-use twilight_mention::Mention;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
 use twilight_model::{
     channel::message::{
         Component, EmojiReactionType,
@@ -11,7 +14,11 @@ use twilight_model::{
     id::{Id, marker::UserMarker},
 };
 
-use crate::{consts, core::app_state::AppState};
+use crate::{
+    consts,
+    core::{app_state::AppState, database::InventoryItem},
+    custom_id::CustomId,
+};
 
 pub async fn run(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
     let mut content = "# Inventory\n".to_string();
@@ -21,6 +28,23 @@ pub async fn run(state: AppState, user_id: Id<UserMarker>) -> Result<Interaction
     let energy = state.cache.energy_balance.get(user_id).await?;
     let _ = writeln!(&mut content, "**Energy:** {energy} ⚡");
 
+    // Energy boost
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    if let Some(boost) = state
+        .cache
+        .energy_boosts
+        .get(user_id)
+        .filter(|boost| boost.is_active(now))
+    {
+        let expires_at = Timestamp::new(boost.expires_at, Some(TimestampStyle::RelativeTime));
+        let _ = writeln!(
+            &mut content,
+            "**Energy Boost:** x{} until {}",
+            boost.multiplier,
+            expires_at.mention()
+        );
+    }
+
     // Custom roles
     if let Some(role) = state.cache.user_custom_roles.get(user_id) {
         let _ = writeln!(&mut content, "**Custom Role:** {}", role.role_id.mention());
@@ -35,11 +59,30 @@ pub async fn run(state: AppState, user_id: Id<UserMarker>) -> Result<Interaction
         });
     }
 
+    // Nickname changes
+    let nickname_tokens = state
+        .db
+        .fetch_inventory_item(user_id, InventoryItem::Nickname)
+        .await?;
+    if nickname_tokens > 0 {
+        let _ = writeln!(&mut content, "**Nickname Change:** {nickname_tokens} 📝");
+        options.push(SelectMenuOption {
+            default: false,
+            description: Some("Change your nickname in the server".to_string()),
+            emoji: Some(EmojiReactionType::Unicode {
+                name: '📝'.to_string(),
+            }),
+            label: "Nickname Change".to_string(),
+            value: consts::interact::INVENTORY_NICKNAME.to_string(),
+        });
+    }
+
     // Build select menu
+    let custom_id = CustomId::Inventory.encode()?;
     let components = options.is_empty().not().then(|| {
         let components = vec![Component::SelectMenu(SelectMenu {
             channel_types: None,
-            custom_id: consts::interact::INVENTORY.to_string(),
+            custom_id,
             default_values: None,
             disabled: false,
             kind: SelectMenuType::Text,
@@ -1,6 +1,64 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use twilight_model::{
+    channel::message::{
+        Component,
+        component::{ActionRow, TextInput, TextInputStyle},
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::UserMarker},
+};
 
-pub fn run() -> Result<InteractionResponse> {
-    todo!()
+use crate::{
+    consts,
+    core::{app_state::AppState, database::InventoryItem},
+    custom_id::CustomId,
+    events::interaction_create::Defer,
+    interactions::ephemeral_response,
+};
+
+pub const MAX_NICKNAME_LENGTH: u16 = 32;
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    defer: &Defer,
+) -> Result<InteractionResponse> {
+    // Answers with a modal, which has to be the first response
+    defer.forbid();
+
+    let tokens = state
+        .db
+        .fetch_inventory_item(user_id, InventoryItem::Nickname)
+        .await?;
+
+    if tokens == 0 {
+        return Ok(lack_of_nickname_token());
+    }
+
+    let text_input = Component::TextInput(TextInput {
+        custom_id: consts::interact::NICKNAME.to_string(),
+        label: "New nickname".to_string(),
+        max_length: Some(MAX_NICKNAME_LENGTH),
+        min_length: Some(1),
+        placeholder: None,
+        required: Some(true),
+        style: TextInputStyle::Short,
+        value: None,
+    });
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(InteractionResponseData {
+            custom_id: Some(CustomId::ChangeNickname.encode()?),
+            title: Some("Change Nickname".to_string()),
+            components: Some(vec![Component::ActionRow(ActionRow {
+                components: vec![text_input],
+            })]),
+            ..Default::default()
+        }),
+    })
+}
+
+pub fn lack_of_nickname_token() -> InteractionResponse {
+    ephemeral_response("You don't have any nickname change left. Grab one from the shop!")
 }
@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::message::{
+        Component, EmojiReactionType, MessageFlags,
+        component::{ActionRow, Button, ButtonStyle},
+        embed::EmbedField,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+use crate::{
+    consts,
+    core::{app_state::AppState, database::LeaderboardKind},
+    custom_id::CustomId,
+    events::interaction_create::{Defer, LeaderboardPage},
+};
+
+/// Members shown on a single page
+const PAGE_SIZE: u64 = 10;
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    page: LeaderboardPage,
+    defer: &Defer,
+) -> Result<InteractionResponse> {
+    let LeaderboardPage { kind, page, update } = page;
+    match update {
+        true => defer.update(),
+        false => defer.message(true),
+    }
+
+    // Rank from the database, with the latest changes in
+    state.cache.energy_balance.sync_energy_data().await?;
+
+    let size = state.db.fetch_leaderboard_size(kind).await?;
+    let last_page = size.saturating_sub(1) / PAGE_SIZE;
+    let page = page.min(last_page);
+
+    let entries = state
+        .db
+        .fetch_leaderboard(kind, page * PAGE_SIZE, PAGE_SIZE)
+        .await?;
+    let own_entry = state.db.fetch_leaderboard_rank(kind, user_id).await?;
+
+    let mut description = String::new();
+    for entry in &entries {
+        let medal = match entry.rank {
+            1 => "🥇",
+            2 => "🥈",
+            3 => "🥉",
+            _ => "",
+        };
+        let line = format!(
+            "**#{}** {medal} {} — {} ⚡",
+            entry.rank,
+            entry.user_id.mention(),
+            entry.score
+        );
+        // Highlight the caller
+        let _ = match entry.user_id == user_id {
+            true => writeln!(&mut description, "👉 __{line}__"),
+            false => writeln!(&mut description, "{line}"),
+        };
+    }
+    if entries.is_empty() {
+        description.push_str("Nobody is ranked yet. Be the first!");
+    }
+
+    let own_rank = match own_entry {
+        Some(entry) => format!("#{} with {} ⚡", entry.rank, entry.score),
+        None => "Unranked".to_string(),
+    };
+    let title = match kind {
+        LeaderboardKind::Balance => "Energy Leaderboard ⚡",
+        LeaderboardKind::Lifetime => "Lifetime Energy Leaderboard ⚡",
+    };
+    let embed = EmbedBuilder::new()
+        .title(title)
+        .description(description)
+        .field(EmbedField {
+            inline: false,
+            name: "Your Rank".to_string(),
+            value: own_rank,
+        })
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{}",
+            page + 1,
+            last_page + 1
+        )))
+        .color(consts::colors::MENU_COLOR)
+        .build();
+
+    let other_kind = match kind {
+        LeaderboardKind::Balance => (LeaderboardKind::Lifetime, "Lifetime", '🏆'),
+        LeaderboardKind::Lifetime => (LeaderboardKind::Balance, "Balance", '⚡'),
+    };
+    let page_id = |kind, page| CustomId::LeaderboardPage { kind, page };
+    let buttons = [
+        (
+            "Previous",
+            page_id(kind, page.saturating_sub(1)),
+            page == 0,
+            '⬅',
+        ),
+        ("Next", page_id(kind, page + 1), page >= last_page, '➡'),
+        (other_kind.1, page_id(other_kind.0, 0), false, other_kind.2),
+    ];
+    let components = buttons
+        .into_iter()
+        .map(|(label, custom_id, disabled, emoji)| {
+            Ok(Component::Button(Button {
+                custom_id: Some(custom_id.encode()?),
+                disabled,
+                emoji: Some(EmojiReactionType::Unicode {
+                    name: emoji.to_string(),
+                }),
+                label: Some(label.to_string()),
+                style: ButtonStyle::Secondary,
+                url: None,
+                sku_id: None,
+            }))
+        })
+        .collect::<Result<_>>()?;
+
+    // Page buttons edit the leaderboard in place
+    let kind = match update {
+        true => InteractionResponseType::UpdateMessage,
+        false => InteractionResponseType::ChannelMessageWithSource,
+    };
+    Ok(InteractionResponse {
+        kind,
+        data: Some(InteractionResponseData {
+            embeds: Some(vec![embed]),
+            components: Some(vec![Component::ActionRow(ActionRow { components })]),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    })
+}
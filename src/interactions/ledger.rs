@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    core::app_state::AppState, events::interaction_create::Defer, interactions::ephemeral_response,
+};
+
+/// Number of entries shown by the ledger command
+const LEDGER_PAGE_SIZE: u64 = 15;
+
+pub async fn run(
+    state: AppState,
+    user_id: Id<UserMarker>,
+    defer: &Defer,
+) -> Result<InteractionResponse> {
+    defer.message(true);
+
+    // Flush buffered entries so the ledger and the balance can be compared
+    state.cache.energy_balance.sync_energy_data().await?;
+
+    let entries = state.db.fetch_ledger(user_id, LEDGER_PAGE_SIZE).await?;
+    let ledger_balance = state.db.fetch_ledger_balance(user_id).await?;
+    let balance = state.cache.energy_balance.get(user_id).await?;
+
+    let mut content = format!("# Ledger of {}\n", user_id.mention());
+    let _ = writeln!(&mut content, "**Balance:** {balance} ⚡");
+    if ledger_balance != balance as i64 {
+        let _ = writeln!(
+            &mut content,
+            "⚠️ The ledger adds up to {ledger_balance} ⚡, which doesn't match the balance."
+        );
+    }
+
+    if entries.is_empty() {
+        content.push_str("No entries yet.");
+    }
+    for entry in entries {
+        let created_at = Timestamp::new(entry.created_at, Some(TimestampStyle::ShortDateTime));
+        let _ = write!(
+            &mut content,
+            "\n{} `{:+}` ⚡ {}",
+            created_at.mention(),
+            entry.delta,
+            entry.reason
+        );
+        if let Some(reference) = entry.reference {
+            let _ = write!(&mut content, " (`{reference}`)");
+        }
+    }
+
+    Ok(ephemeral_response(content))
+}
@@ -12,7 +12,7 @@ use twilight_model::{
 };
 use twilight_util::builder::embed::EmbedBuilder;
 
-use crate::{consts, core::app_state::AppState};
+use crate::{consts, core::app_state::AppState, custom_id::CustomId};
 
 pub fn run(state: AppState) -> Result<InteractionResponse> {
     let config_lock = state.config.read().unwrap();
@@ -27,8 +27,12 @@ pub fn run(state: AppState) -> Result<InteractionResponse> {
 
     // Zones field
     let mut zones = String::new();
-    config_lock.zones.iter().for_each(|zone| {
-        let _ = write!(&mut zones, "{}", zone.mention());
+    config_lock.zones.iter().for_each(|(channel_id, zone)| {
+        let _ = write!(&mut zones, "{} {} ⚡", channel_id.mention(), zone.energy);
+        if let Some(cooldown) = zone.cooldown {
+            let _ = write!(&mut zones, " every {cooldown}s");
+        }
+        zones.push('\n');
     });
     drop(config_lock);
 
@@ -53,27 +57,39 @@ pub fn run(state: AppState) -> Result<InteractionResponse> {
 
     // Build components
     #[rustfmt::skip]
-    const MENU_ITEMS: [(&str, &str, ButtonStyle, char); 5] = [
-        ("Inventory", consts::interact::INVENTORY, ButtonStyle::Primary, '📦'),
-        ("Shop", consts::interact::SHOP, ButtonStyle::Primary, '🛒'),
-        ("Games", consts::interact::GAMES, ButtonStyle::Primary, '🎮'),
-        ("About", consts::interact::ABOUT, ButtonStyle::Secondary, '📙'),
-        ("FAQs", consts::interact::FAQS, ButtonStyle::Secondary, '❓'),
+    const MENU_ITEMS: [(&str, CustomId, ButtonStyle, char); 4] = [
+        ("Inventory", CustomId::Inventory, ButtonStyle::Primary, '📦'),
+        ("Shop", CustomId::Shop, ButtonStyle::Primary, '🛒'),
+        ("Leaderboard", CustomId::Leaderboard, ButtonStyle::Primary, '🏆'),
+        ("Games", CustomId::Games, ButtonStyle::Primary, '🎮'),
     ];
-    let components = Vec::from(MENU_ITEMS.map(|(label, custom_id, style, emoji)| {
-        Component::Button(Button {
-            custom_id: Some(custom_id.to_string()),
-            disabled: false,
-            emoji: Some(EmojiReactionType::Unicode {
-                name: emoji.to_string(),
-            }),
-            label: Some(label.to_string()),
-            style,
-            url: None,
-            sku_id: None,
+    #[rustfmt::skip]
+    const INFO_ITEMS: [(&str, CustomId, ButtonStyle, char); 2] = [
+        ("About", CustomId::About, ButtonStyle::Secondary, '📙'),
+        ("FAQs", CustomId::Faqs, ButtonStyle::Secondary, '❓'),
+    ];
+    let components = [&MENU_ITEMS[..], &INFO_ITEMS[..]]
+        .into_iter()
+        .map(|items| {
+            let components = items
+                .iter()
+                .map(|&(label, custom_id, style, emoji)| {
+                    Ok(Component::Button(Button {
+                        custom_id: Some(custom_id.encode()?),
+                        disabled: false,
+                        emoji: Some(EmojiReactionType::Unicode {
+                            name: emoji.to_string(),
+                        }),
+                        label: Some(label.to_string()),
+                        style,
+                        url: None,
+                        sku_id: None,
+                    }))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Component::ActionRow(ActionRow { components }))
         })
-    }));
-    let components = vec![Component::ActionRow(ActionRow { components })];
+        .collect::<Result<_>>()?;
 
     // Build the response
     let response = InteractionResponse {
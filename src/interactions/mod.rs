@@ -3,14 +3,25 @@ use twilight_model::{
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
 };
 
+pub mod confirm_custom_role_edit;
 pub mod confirm_inventory_nickname;
 
 pub mod confirm_order;
 pub mod custom_role;
+pub mod custom_role_editor;
 pub mod custom_role_subscribe;
+pub mod filter_stats;
+pub mod give;
+pub mod inspect;
 pub mod inventory;
 pub mod inventory_nickname;
+pub mod leaderboard;
+pub mod ledger;
 pub mod menu;
+pub mod mod_config;
+pub mod mod_custom_role;
+pub mod mod_energy;
+pub mod reconcile;
 pub mod shop;
 pub mod shop_blind_box;
 pub mod shop_custom_role;
@@ -20,10 +31,14 @@ pub mod unimplemented;
 pub mod zones;
 
 pub fn server_error_response() -> InteractionResponse {
+    ephemeral_response("Oops! Something went wrong.")
+}
+
+pub fn ephemeral_response(content: impl Into<String>) -> InteractionResponse {
     InteractionResponse {
         kind: InteractionResponseType::ChannelMessageWithSource,
         data: Some(InteractionResponseData {
-            content: Some("Oops! Something went wrong.".to_string()),
+            content: Some(content.into()),
             flags: Some(MessageFlags::EPHEMERAL),
             ..Default::default()
         }),
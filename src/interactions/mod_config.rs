@@ -0,0 +1,42 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    core::{app_state::AppState, config::EDITABLE_KEYS},
+    interactions::ephemeral_response,
+};
+
+pub async fn view(state: AppState, moderator_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    let mut content = "# Config\n".to_string();
+    for key in EDITABLE_KEYS {
+        let value = state.config.value_of(key).unwrap_or_default();
+        let _ = writeln!(&mut content, "**{key}:** `{value}`");
+    }
+
+    state
+        .moderation_log(moderator_id, "viewed the config")
+        .await;
+    Ok(ephemeral_response(content))
+}
+
+pub async fn set(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    key: String,
+    value: String,
+) -> Result<InteractionResponse> {
+    let stored = match state.config.set(&key, &value).await {
+        Ok(stored) => stored,
+        Err(err) => return Ok(ephemeral_response(format!("{err}."))),
+    };
+
+    state
+        .moderation_log(moderator_id, &format!("set `{key}` to `{stored}`"))
+        .await;
+    Ok(ephemeral_response(format!("**{key}** is now `{stored}`.")))
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use tracing::{error, warn};
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{consts::DAY_IN_SEC, core::app_state::AppState, interactions::ephemeral_response};
+
+/// Removes the custom role of a member right away, whatever its renewal settings.
+pub async fn expire(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    target: Id<UserMarker>,
+) -> Result<InteractionResponse> {
+    // Drop it from the cache first so the RoleDelete event doesn't DM the owner
+    let Some(role) = state.cache.user_custom_roles.remove(target) else {
+        return Ok(ephemeral_response(format!(
+            "{} doesn't own a custom role.",
+            target.mention()
+        )));
+    };
+
+    let guild_id = state.config.env.guild_id;
+    if let Err(err) = state.app.delete_role(guild_id, role.role_id).await {
+        state.cache.user_custom_roles.update(role);
+        return Err(err.into());
+    }
+    // The role is gone from Discord at this point, whatever happens to the record
+    state
+        .moderation_log(
+            moderator_id,
+            &format!(
+                "expired the custom role **{}** of {}",
+                role.name,
+                target.mention()
+            ),
+        )
+        .await;
+
+    let content = format!(
+        "Your custom role **{}** was removed by a moderator.",
+        role.name
+    );
+    if let Err(err) = state.direct_message(target, &content).await {
+        warn!(?err, "Unable to notify <{target}> about their expired role");
+    }
+
+    if let Err(err) = state.db.delete_custom_role_by_role_id(role.role_id).await {
+        error!(
+            ?err,
+            "Unable to delete the record of custom role {}", role.role_id
+        );
+        return Ok(ephemeral_response(format!(
+            "The custom role **{}** of {} has been removed from the server, \
+            but its record couldn't be deleted: {err}",
+            role.name,
+            target.mention()
+        )));
+    }
+
+    Ok(ephemeral_response(format!(
+        "The custom role **{}** of {} has been removed.",
+        role.name,
+        target.mention()
+    )))
+}
+
+/// Pushes back the expiry of a member's custom role by `days`.
+pub async fn extend(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    target: Id<UserMarker>,
+    days: u64,
+) -> Result<InteractionResponse> {
+    if days == 0 {
+        return Ok(ephemeral_response("The extension must be at least 1 day."));
+    }
+
+    let Some(mut role) = state.cache.user_custom_roles.get(target) else {
+        return Ok(ephemeral_response(format!(
+            "{} doesn't own a custom role.",
+            target.mention()
+        )));
+    };
+    let Some(expires_at) = role.expires_at else {
+        return Ok(ephemeral_response(format!(
+            "The custom role of {} never expires.",
+            target.mention()
+        )));
+    };
+
+    // A role already past its expiry gets the days from now, not from the past
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let expires_at = expires_at.max(now) + days * DAY_IN_SEC;
+    role.expires_at = Some(expires_at);
+    state.db.update_custom_role(&role).await?;
+    state.cache.user_custom_roles.update(role);
+
+    let expires_at = Timestamp::new(expires_at, Some(TimestampStyle::ShortDateTime)).mention();
+    state
+        .moderation_log(
+            moderator_id,
+            &format!(
+                "extended the custom role of {} by {days} day(s), until {expires_at}",
+                target.mention()
+            ),
+        )
+        .await;
+    Ok(ephemeral_response(format!(
+        "The custom role of {} now expires {expires_at}.",
+        target.mention()
+    )))
+}
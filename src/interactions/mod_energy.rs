@@ -0,0 +1,85 @@
+use anyhow::Result;
+use twilight_mention::Mention;
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    core::{app_state::AppState, database::LedgerReason},
+    interactions::ephemeral_response,
+};
+
+/// Adds energy to a member, the ledger entry references the moderator.
+pub async fn grant(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    target: Id<UserMarker>,
+    amount: u64,
+) -> Result<InteractionResponse> {
+    if amount == 0 {
+        return Ok(ephemeral_response("The amount must be at least 1 ⚡."));
+    }
+
+    let energy_balance = &state.cache.energy_balance;
+    energy_balance
+        .add_energy(
+            target,
+            amount,
+            LedgerReason::AdminGrant,
+            Some(moderator_id.get()),
+        )
+        .await?;
+    let balance = energy_balance.get(target).await?;
+
+    state
+        .moderation_log(
+            moderator_id,
+            &format!("granted {amount} ⚡ to {}", target.mention()),
+        )
+        .await;
+    Ok(ephemeral_response(format!(
+        "Granted {amount} ⚡ to {}, who now has {balance} ⚡.",
+        target.mention()
+    )))
+}
+
+/// Takes energy from a member, never more than they hold.
+pub async fn revoke(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    target: Id<UserMarker>,
+    amount: u64,
+) -> Result<InteractionResponse> {
+    if amount == 0 {
+        return Ok(ephemeral_response("The amount must be at least 1 ⚡."));
+    }
+
+    let energy_balance = &state.cache.energy_balance;
+    let taken = energy_balance
+        .take_energy(
+            target,
+            amount,
+            LedgerReason::AdminRevoke,
+            Some(moderator_id.get()),
+        )
+        .await?;
+    if taken == 0 {
+        return Ok(ephemeral_response(format!(
+            "{} has no energy to revoke.",
+            target.mention()
+        )));
+    }
+    let balance = energy_balance.get(target).await?;
+
+    state
+        .moderation_log(
+            moderator_id,
+            &format!("revoked {taken} ⚡ from {}", target.mention()),
+        )
+        .await;
+    Ok(ephemeral_response(format!(
+        "Revoked {taken} ⚡ from {}, who now has {balance} ⚡.",
+        target.mention()
+    )))
+}
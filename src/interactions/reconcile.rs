@@ -0,0 +1,14 @@
+use anyhow::Result;
+use twilight_model::http::interaction::InteractionResponse;
+
+use crate::{
+    core::app_state::AppState, events::interaction_create::Defer, interactions::ephemeral_response,
+    reconcile,
+};
+
+pub async fn run(state: AppState, defer: &Defer) -> Result<InteractionResponse> {
+    defer.message(true);
+
+    let report = reconcile::run_and_report(&state).await?;
+    Ok(ephemeral_response(report.to_string()))
+}
@@ -7,7 +7,7 @@ use twilight_model::{
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
 };
 
-use crate::consts;
+use crate::{consts, custom_id::CustomId};
 
 pub fn run() -> Result<InteractionResponse> {
     let content = "Welcome to the shop! Here you can purchase items, roles, and more.".to_string();
@@ -35,7 +35,7 @@ pub fn run() -> Result<InteractionResponse> {
 
     let select_menu = Component::SelectMenu(SelectMenu {
         channel_types: None,
-        custom_id: consts::interact::SHOP.to_string(),
+        custom_id: CustomId::Shop.encode()?,
         default_values: None,
         disabled: false,
         kind: SelectMenuType::Text,
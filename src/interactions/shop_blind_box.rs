@@ -1,6 +1,161 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use tracing::error;
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
 
-pub fn run() -> Result<InteractionResponse> {
-    todo!()
+use crate::{
+    core::{
+        app_state::AppState,
+        blind_box::{LootEntry, Reward},
+        database::{EnergyBoost, InventoryItem, LedgerReason},
+    },
+    events::interaction_create::OrderItem,
+    interactions::{confirm_order, ephemeral_response},
+};
+
+pub fn run(state: AppState) -> Result<InteractionResponse> {
+    let fee = state.config.read().unwrap().service_fee.blind_box;
+    confirm_order::modal(OrderItem::BlindBox, "Blind Box", fee)
+}
+
+pub async fn purchase(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    let (fee, pity_threshold) = {
+        let config = state.config.read().unwrap();
+        (config.service_fee.blind_box, config.blind_box_pity)
+    };
+    let loot_table = state.db.fetch_loot_table().await?;
+    let owned_role = state.cache.user_custom_roles.get(user_id);
+
+    // Role extensions are only worth something to owners of an expiring role
+    let eligible = |reward: &Reward| match reward {
+        Reward::CustomRoleExtension(_) => owned_role
+            .as_ref()
+            .is_some_and(|role| role.expires_at.is_some()),
+        _ => true,
+    };
+
+    let pity_counter = state.db.fetch_blind_box_pity(user_id).await?;
+    let roll = {
+        let mut rng = state.rng.lock().unwrap();
+        let pity = pity_threshold > 0 && pity_counter + 1 >= pity_threshold;
+        loot_table.roll(&mut *rng, pity, |entry| eligible(&entry.reward))
+    };
+    let Some(roll) = roll else {
+        return Ok(ephemeral_response(
+            "Blind boxes are out of stock, come back later!",
+        ));
+    };
+
+    let paid = state
+        .cache
+        .energy_balance
+        .consume_energy(user_id, fee, LedgerReason::Purchase, None)
+        .await?;
+
+    if !paid {
+        return Ok(ephemeral_response(format!(
+            "You need at least {fee} ⚡ to open a blind box."
+        )));
+    }
+
+    // The guarantee only holds if the counter is saved along with every open
+    let new_pity_counter = match roll.entry.rarity.is_rare() {
+        true => 0,
+        false => pity_counter + 1,
+    };
+    if let Err(err) = state
+        .db
+        .update_blind_box_pity(user_id, new_pity_counter)
+        .await
+    {
+        refund(&state, user_id, fee).await;
+        return Err(err);
+    }
+
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    if let Err(err) = grant_reward(&state, user_id, roll.entry, now).await {
+        refund(&state, user_id, fee).await;
+        if let Err(restore_err) = state.db.update_blind_box_pity(user_id, pity_counter).await {
+            error!(
+                ?restore_err,
+                "Unable to restore the pity counter of <{user_id}>"
+            );
+        }
+        return Err(err);
+    }
+
+    // The reward is already delivered, a failed audit record must not take it back
+    if let Err(err) = state.db.record_blind_box_roll(user_id, &roll, now).await {
+        error!(?err, "Unable to record blind box roll of <{user_id}>");
+    }
+
+    let pity_note = match pity_threshold {
+        0 => String::new(),
+        _ => match pity_threshold.saturating_sub(new_pity_counter) {
+            0 | 1 => "\nYour next box is guaranteed to be rare!".to_string(),
+            left => format!("\nA rare drop is guaranteed within {left} opens."),
+        },
+    };
+    Ok(ephemeral_response(format!(
+        "🎁 You opened a blind box and got a **{}** drop: {}!{pity_note}",
+        roll.entry.rarity, roll.entry.reward
+    )))
+}
+
+async fn refund(state: &AppState, user_id: Id<UserMarker>, fee: u64) {
+    if let Err(err) = state
+        .cache
+        .energy_balance
+        .add_energy(user_id, fee, LedgerReason::Refund, None)
+        .await
+    {
+        error!(?err, "Unable to refund {fee} energy to <{user_id}>");
+    }
+}
+
+async fn grant_reward(
+    state: &AppState,
+    user_id: Id<UserMarker>,
+    entry: LootEntry,
+    now: u64,
+) -> Result<()> {
+    match entry.reward {
+        Reward::Energy(amount) => {
+            state
+                .cache
+                .energy_balance
+                .add_energy(user_id, amount, LedgerReason::Reward, Some(entry.loot_id))
+                .await?;
+        }
+        Reward::Nickname(amount) => {
+            state
+                .db
+                .add_inventory_item(user_id, InventoryItem::Nickname, amount)
+                .await?;
+        }
+        Reward::EnergyBoost(duration) => {
+            let multiplier = state.config.read().unwrap().energy_boost.multiplier;
+            let boost = EnergyBoost::extend(
+                state.cache.energy_boosts.get(user_id),
+                user_id,
+                multiplier,
+                duration,
+                now,
+            );
+            state.db.update_energy_boost(&boost).await?;
+            state.cache.energy_boosts.update(boost);
+        }
+        Reward::CustomRoleExtension(secs) => {
+            let Some(mut role) = state.cache.user_custom_roles.get(user_id) else {
+                return Err(anyhow::anyhow!("<{user_id}> no longer owns a custom role"));
+            };
+            role.expires_at = role.expires_at.map(|expires_at| expires_at + secs);
+            state.db.update_custom_role(&role).await?;
+            state.cache.user_custom_roles.update(role);
+        }
+    }
+
+    Ok(())
 }
@@ -1,6 +1,127 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use tracing::error;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
 
-pub fn run() -> Result<InteractionResponse> {
-    todo!()
+use crate::{
+    consts::MONTH_IN_SEC,
+    core::{
+        app_state::AppState,
+        database::{CustomRole, LedgerReason, RoleIcon},
+    },
+    events::interaction_create::OrderItem,
+    interactions::{confirm_order, ephemeral_response},
+};
+
+const DEFAULT_ROLE_NAME: &str = "Custom Role";
+
+pub fn run(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    if state.cache.user_custom_roles.get(user_id).is_some() {
+        return Ok(already_owned());
+    }
+
+    let fee = state.config.read().unwrap().service_fee.custom_role;
+    confirm_order::modal(OrderItem::CustomRole, "Custom Role", fee)
+}
+
+pub async fn purchase(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    if state.cache.user_custom_roles.get(user_id).is_some() {
+        return Ok(already_owned());
+    }
+
+    let fee = state.config.read().unwrap().service_fee.custom_role;
+    let paid = state
+        .cache
+        .energy_balance
+        .consume_energy(user_id, fee, LedgerReason::Purchase, None)
+        .await?;
+
+    if !paid {
+        return Ok(ephemeral_response(format!(
+            "You need at least {fee} ⚡ to buy a custom role."
+        )));
+    }
+
+    // Give the energy back if the role could not be delivered
+    let role = match grant_role(&state, user_id).await {
+        Ok(role) => role,
+        Err(err) => {
+            if let Err(refund_err) = state
+                .cache
+                .energy_balance
+                .add_energy(user_id, fee, LedgerReason::Refund, None)
+                .await
+            {
+                error!(?refund_err, "Unable to refund {fee} energy to <{user_id}>");
+            }
+            return Err(err);
+        }
+    };
+
+    let expires_at = Timestamp::new(
+        role.expires_at.unwrap_or_default(),
+        Some(TimestampStyle::ShortDate),
+    );
+    Ok(ephemeral_response(format!(
+        "🎀 Your custom role {role} is ready! It lasts until {expires_at}.\n\
+        You can manage it from your inventory.",
+        role = role.role_id.mention(),
+        expires_at = expires_at.mention(),
+    )))
+}
+
+/// Creates the guild role, assigns it to the user and persists it.
+///
+/// The guild role is deleted again if any of the later steps fails.
+async fn grant_role(state: &AppState, user_id: Id<UserMarker>) -> Result<CustomRole> {
+    let guild_id = state.config.env.guild_id;
+    let role = state
+        .app
+        .create_role(guild_id)
+        .name(DEFAULT_ROLE_NAME)
+        .await?
+        .model()
+        .await?;
+
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let crole = CustomRole {
+        role_id: role.id,
+        user_id,
+        auto_renewal: true,
+        expires_at: Some(now + MONTH_IN_SEC),
+        name: role.name,
+        color: role.color,
+        icon: RoleIcon::None,
+        mentionable: role.mentionable,
+    };
+
+    let rs = async {
+        state
+            .app
+            .add_guild_member_role(guild_id, user_id, crole.role_id)
+            .await?;
+        state.db.update_custom_role(&crole).await?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    if let Err(err) = rs {
+        if let Err(delete_err) = state.app.delete_role(guild_id, crole.role_id).await {
+            error!(?delete_err, "Unable to roll back role <{}>", crole.role_id);
+        }
+        return Err(err);
+    }
+
+    state.cache.user_custom_roles.update(crole.clone());
+    Ok(crole)
+}
+
+fn already_owned() -> InteractionResponse {
+    ephemeral_response("You already own a custom role! Manage it from your inventory.")
 }
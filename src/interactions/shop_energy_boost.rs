@@ -1,6 +1,85 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use tracing::error;
+use twilight_mention::{
+    Mention,
+    timestamp::{Timestamp, TimestampStyle},
+};
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
 
-pub fn run() -> Result<InteractionResponse> {
-    todo!()
+use crate::{
+    consts::HOUR_IN_SEC,
+    core::{
+        app_state::AppState,
+        database::{EnergyBoost, LedgerReason},
+    },
+    events::interaction_create::OrderItem,
+    interactions::{confirm_order, ephemeral_response},
+};
+
+pub fn run(state: AppState) -> Result<InteractionResponse> {
+    let (fee, boost) = {
+        let config = state.config.read().unwrap();
+        (config.service_fee.energy_boost, config.energy_boost)
+    };
+
+    confirm_order::modal(
+        OrderItem::EnergyBoost,
+        &format!(
+            "x{} Energy Boost ({}h)",
+            boost.multiplier,
+            boost.duration / HOUR_IN_SEC
+        ),
+        fee,
+    )
+}
+
+pub async fn purchase(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    let (fee, config) = {
+        let config = state.config.read().unwrap();
+        (config.service_fee.energy_boost, config.energy_boost)
+    };
+    let paid = state
+        .cache
+        .energy_balance
+        .consume_energy(user_id, fee, LedgerReason::Purchase, None)
+        .await?;
+
+    if !paid {
+        return Ok(ephemeral_response(format!(
+            "You need at least {fee} ⚡ to buy an energy boost."
+        )));
+    }
+
+    let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let boost = EnergyBoost::extend(
+        state.cache.energy_boosts.get(user_id),
+        user_id,
+        config.multiplier,
+        config.duration,
+        now,
+    );
+
+    if let Err(err) = state.db.update_energy_boost(&boost).await {
+        if let Err(refund_err) = state
+            .cache
+            .energy_balance
+            .add_energy(user_id, fee, LedgerReason::Refund, None)
+            .await
+        {
+            error!(?refund_err, "Unable to refund {fee} energy to <{user_id}>");
+        }
+        return Err(err);
+    }
+    state.cache.energy_boosts.update(boost);
+
+    let expires_at = Timestamp::new(boost.expires_at, Some(TimestampStyle::RelativeTime));
+    Ok(ephemeral_response(format!(
+        "⚡ Energy boost active! Your check-ins earn x{} energy, ending {}.\n\
+        Buying another boost while one is active extends it instead of stacking.",
+        boost.multiplier,
+        expires_at.mention()
+    )))
 }
@@ -1,6 +1,56 @@
 use anyhow::Result;
-use twilight_model::http::interaction::InteractionResponse;
+use tracing::error;
+use twilight_model::{
+    http::interaction::InteractionResponse,
+    id::{Id, marker::UserMarker},
+};
 
-pub fn run() -> Result<InteractionResponse> {
-    todo!()
+use crate::{
+    core::{
+        app_state::AppState,
+        database::{InventoryItem, LedgerReason},
+    },
+    events::interaction_create::OrderItem,
+    interactions::{confirm_order, ephemeral_response},
+};
+
+pub fn run(state: AppState) -> Result<InteractionResponse> {
+    let fee = state.config.read().unwrap().service_fee.nickname;
+    confirm_order::modal(OrderItem::Nickname, "Nickname Change", fee)
+}
+
+pub async fn purchase(state: AppState, user_id: Id<UserMarker>) -> Result<InteractionResponse> {
+    let fee = state.config.read().unwrap().service_fee.nickname;
+    let paid = state
+        .cache
+        .energy_balance
+        .consume_energy(user_id, fee, LedgerReason::Purchase, None)
+        .await?;
+
+    if !paid {
+        return Ok(ephemeral_response(format!(
+            "You need at least {fee} ⚡ to buy a nickname change."
+        )));
+    }
+
+    let rs = state
+        .db
+        .add_inventory_item(user_id, InventoryItem::Nickname, 1)
+        .await;
+
+    if let Err(err) = rs {
+        if let Err(refund_err) = state
+            .cache
+            .energy_balance
+            .add_energy(user_id, fee, LedgerReason::Refund, None)
+            .await
+        {
+            error!(?refund_err, "Unable to refund {fee} energy to <{user_id}>");
+        }
+        return Err(err);
+    }
+
+    Ok(ephemeral_response(
+        "📝 You bought a nickname change! Use it from your inventory whenever you like.",
+    ))
 }
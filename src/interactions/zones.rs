@@ -1,5 +1,168 @@
-use twilight_model::http::interaction::InteractionResponse;
+use std::fmt::Write;
 
-pub fn run() -> InteractionResponse {
-    todo!()
+use anyhow::Result;
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::{
+        ChannelType,
+        message::{
+            Component, MessageFlags,
+            component::{ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuType},
+        },
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    core::app_state::AppState, custom_id::CustomId, events::interaction_create::ZonesAction,
+};
+
+/// Remove buttons fitting under the channel select menu
+const MAX_REMOVE_BUTTONS: usize = 20;
+const BUTTONS_PER_ROW: usize = 5;
+
+pub async fn run(
+    state: AppState,
+    moderator_id: Id<UserMarker>,
+    action: ZonesAction,
+) -> Result<InteractionResponse> {
+    let update = !matches!(action, ZonesAction::View);
+    match action {
+        ZonesAction::View => {}
+        ZonesAction::Add(channels) => {
+            let added = {
+                let config = state.config.read().unwrap();
+                channels
+                    .into_iter()
+                    .filter(|channel_id| !config.zones.contains_key(channel_id))
+                    .collect::<Vec<_>>()
+            };
+            if !added.is_empty() {
+                state
+                    .config
+                    .update_zones(|zones| {
+                        for &channel_id in &added {
+                            zones.entry(channel_id).or_default();
+                        }
+                    })
+                    .await?;
+
+                let channels = added
+                    .iter()
+                    .map(|channel_id| channel_id.mention().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                state
+                    .moderation_log(moderator_id, &format!("added the zones {channels}"))
+                    .await;
+            }
+        }
+        ZonesAction::Remove(channel_id) => {
+            let mut removed = false;
+            state
+                .config
+                .update_zones(|zones| removed = zones.remove(&channel_id).is_some())
+                .await?;
+
+            if removed {
+                state
+                    .moderation_log(
+                        moderator_id,
+                        &format!("removed the zone {}", channel_id.mention()),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    let mut zones = state
+        .config
+        .read()
+        .unwrap()
+        .zones
+        .iter()
+        .map(|(&channel_id, &zone)| (channel_id, zone))
+        .collect::<Vec<_>>();
+    zones.sort_unstable_by_key(|(channel_id, _)| *channel_id);
+
+    let mut content = "# Zones\n".to_string();
+    if zones.is_empty() {
+        content.push_str("No zones yet, pick channels below to add some.");
+    }
+    for (idx, (channel_id, zone)) in zones.iter().enumerate() {
+        let _ = write!(
+            &mut content,
+            "`{}.` {} {} ⚡",
+            idx + 1,
+            channel_id.mention(),
+            zone.energy
+        );
+        let _ = match zone.cooldown {
+            Some(cooldown) => writeln!(&mut content, " every {cooldown}s"),
+            None => writeln!(&mut content),
+        };
+    }
+    if zones.len() > MAX_REMOVE_BUTTONS {
+        let _ = write!(
+            &mut content,
+            "\nOnly the first {MAX_REMOVE_BUTTONS} zones can be removed from here."
+        );
+    }
+
+    let add_menu = Component::SelectMenu(SelectMenu {
+        channel_types: Some(vec![
+            ChannelType::GuildText,
+            ChannelType::GuildAnnouncement,
+            ChannelType::GuildVoice,
+        ]),
+        custom_id: CustomId::ZonesAdd.encode()?,
+        default_values: None,
+        disabled: false,
+        kind: SelectMenuType::Channel,
+        max_values: Some(25),
+        min_values: Some(1),
+        options: None,
+        placeholder: Some("Add channels as zones".to_string()),
+    });
+    let mut components = vec![Component::ActionRow(ActionRow {
+        components: vec![add_menu],
+    })];
+
+    let remove_buttons = zones
+        .iter()
+        .take(MAX_REMOVE_BUTTONS)
+        .enumerate()
+        .map(|(idx, &(channel_id, _))| {
+            Ok(Component::Button(Button {
+                custom_id: Some(CustomId::ZonesRemove(channel_id).encode()?),
+                disabled: false,
+                emoji: None,
+                label: Some(format!("Remove {}", idx + 1)),
+                style: ButtonStyle::Danger,
+                url: None,
+                sku_id: None,
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    components.extend(remove_buttons.chunks(BUTTONS_PER_ROW).map(|buttons| {
+        Component::ActionRow(ActionRow {
+            components: buttons.to_vec(),
+        })
+    }));
+
+    // Panel edits happen in place
+    let kind = match update {
+        true => InteractionResponseType::UpdateMessage,
+        false => InteractionResponseType::ChannelMessageWithSource,
+    };
+    Ok(InteractionResponse {
+        kind,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            components: Some(components),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    })
 }
@@ -1,14 +1,23 @@
 mod background;
+mod commands;
 mod consts;
 mod core;
+mod custom_id;
 mod events;
 mod interactions;
+mod reconcile;
 
 use std::{env, time::Duration};
 
 use anyhow::Result;
-use tracing::{error, info};
-use twilight_gateway::{EventTypeFlags, Intents, Shard, ShardId, StreamExt};
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::watch,
+    task::JoinSet,
+    time::Instant,
+};
+use tracing::{error, info, warn};
+use twilight_gateway::{CloseFrame, Event, EventTypeFlags, Intents, Shard, ShardId, StreamExt};
 
 use crate::core::app_state::{AppState, EnvConfig};
 
@@ -36,26 +45,100 @@ async fn main() -> Result<()> {
         discord_token: env::var("DISCORD_TOKEN")?,
         libsql_url: env::var("LIBSQL_URL")?,
         libsql_auth_token: env::var("LIBSQL_AUTH_TOKEN")?,
+        blind_box_seed: env::var("BLIND_BOX_SEED")
+            .ok()
+            .map(|seed| seed.parse())
+            .transpose()?,
+        commands_dry_run: env::var("COMMANDS_DRY_RUN")
+            .ok()
+            .map(|dry_run| dry_run.parse())
+            .transpose()?
+            .unwrap_or(false),
+        content_filters: env::var("CONTENT_FILTERS")
+            .ok()
+            .map(|enabled| enabled.parse())
+            .transpose()?
+            .unwrap_or(false),
     };
 
-    let app = AppState::new(env).await;
-    background::run(app.clone());
+    let mut intents = Intents::GUILD_MESSAGES | Intents::GUILD_MEMBERS | Intents::GUILDS;
+    // Privileged, the gateway closes with 4014 unless it's enabled in the developer portal
+    if env.content_filters {
+        intents |= Intents::MESSAGE_CONTENT;
+    }
+    let mut shard = Shard::new(ShardId::ONE, env.discord_token.clone(), intents);
+
+    let app = AppState::new(env, shard.sender()).await;
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut background_tasks = background::run(app.clone(), shutdown_rx);
 
-    let intents = Intents::GUILD_MESSAGES | Intents::GUILD_MEMBERS | Intents::GUILDS;
-    let mut shard = Shard::new(ShardId::ONE, app.config.env.discord_token.clone(), intents);
     let wanted_event_types = EventTypeFlags::READY
         | EventTypeFlags::MESSAGE_CREATE
+        | EventTypeFlags::MESSAGE_DELETE
+        | EventTypeFlags::MESSAGE_DELETE_BULK
         | EventTypeFlags::MEMBER_UPDATE
+        | EventTypeFlags::MEMBER_REMOVE
+        | EventTypeFlags::MEMBER_CHUNK
         | EventTypeFlags::INTERACTION_CREATE
         | EventTypeFlags::ROLE_UPDATE
+        | EventTypeFlags::ROLE_DELETE
         | EventTypeFlags::GUILD_CREATE;
 
-    while let Some(item) = shard.next_event(wanted_event_types).await {
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    // Cleared when the signal handlers can't be installed, the bot then runs until killed
+    let mut listening = true;
+    let mut closing = false;
+    // Armed once the close frame is sent
+    let close_timeout = tokio::time::sleep_until(Instant::now());
+    tokio::pin!(close_timeout);
+    let mut handlers = JoinSet::new();
+
+    loop {
+        let item = tokio::select! {
+            item = shard.next_event(wanted_event_types) => item,
+            rs = &mut shutdown, if listening && !closing => {
+                if let Err(err) = rs {
+                    error!(?err, "Unable to listen for shutdown signals");
+                    listening = false;
+                    continue;
+                }
+                info!("Shutting down...");
+                closing = true;
+                close_timeout
+                    .as_mut()
+                    .reset(Instant::now() + consts::SHUTDOWN_TIMEOUT);
+                if let Err(err) = app.gateway.close(CloseFrame::RESUME) {
+                    error!(?err, "Unable to close the gateway");
+                    break;
+                }
+                continue;
+            }
+            _ = &mut close_timeout, if closing => {
+                warn!("The gateway didn't acknowledge the close frame in time");
+                break;
+            }
+        };
+
+        // Reap the handlers that are done
+        while handlers.try_join_next().is_some() {}
+
+        let Some(item) = item else {
+            break;
+        };
         let Ok(event) = item else {
             error!(source = ?item.unwrap_err(), "Error receiving event");
             continue;
         };
 
+        // Stop accepting new events until the close frame comes back
+        if closing {
+            if matches!(event, Event::GatewayClose(_)) {
+                break;
+            }
+            continue;
+        }
+
         // Not from the Discord system or the targetted server
         if event
             .guild_id()
@@ -65,7 +148,7 @@ async fn main() -> Result<()> {
         }
 
         let app = app.clone();
-        tokio::spawn(async move {
+        handlers.spawn(async move {
             let Err(err) = events::event_handler(app, event).await else {
                 return;
             };
@@ -73,5 +156,33 @@ async fn main() -> Result<()> {
         });
     }
 
+    info!(
+        "Waiting for {} event handler(s) to finish...",
+        handlers.len()
+    );
+    while handlers.join_next().await.is_some() {}
+
+    let _ = shutdown_tx.send(true);
+    while background_tasks.join_next().await.is_some() {}
+
+    info!("Syncing energy...");
+    if let Err(err) = app.cache.energy_balance.sync_energy_data().await {
+        error!(?err, "Unable to sync energy on shutdown");
+    }
+    if let Err(err) = app.checkin_note.sync().await {
+        error!(?err, "Unable to sync check-ins on shutdown");
+    }
+
+    info!("Bye!");
+    Ok(())
+}
+
+/// Resolves once a SIGINT or a SIGTERM is received.
+async fn shutdown_signal() -> Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        rs = tokio::signal::ctrl_c() => rs?,
+        _ = terminate.recv() => {}
+    }
     Ok(())
 }
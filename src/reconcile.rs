@@ -0,0 +1,182 @@
+use std::{collections::HashSet, fmt::Display};
+
+use anyhow::Result;
+use tracing::{error, info};
+use twilight_http::error::ErrorType;
+use twilight_mention::Mention;
+
+use crate::core::{app_state::AppState, database::CustomRole};
+
+/// Longest report sent in a single Discord message
+const MAX_REPORT_LENGTH: usize = 1900;
+
+#[derive(Debug, Clone)]
+pub enum Drift {
+    /// The `custom_roles` row points to a role that no longer exists in the guild
+    MissingRole(CustomRole),
+    /// The owner of the role is no longer a member of the guild
+    OwnerLeft(CustomRole),
+    /// The owner is still a member but doesn't have the role assigned
+    RoleNotAssigned(CustomRole),
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::MissingRole(role) => write!(
+                f,
+                "Role `{}` of {} no longer exists",
+                role.role_id,
+                role.user_id.mention()
+            ),
+            Drift::OwnerLeft(role) => write!(
+                f,
+                "Owner {} of {} left the server",
+                role.user_id.mention(),
+                role.role_id.mention()
+            ),
+            Drift::RoleNotAssigned(role) => write!(
+                f,
+                "{} doesn't have their role {} assigned",
+                role.user_id.mention(),
+                role.role_id.mention()
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub auto_repair: bool,
+    /// Each drift with the outcome of its repair, if one was attempted
+    pub drifts: Vec<(Drift, Option<Result<()>>)>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = if self.auto_repair {
+            "auto-repair"
+        } else {
+            "report only"
+        };
+        write!(f, "**Custom role reconciliation** ({mode})")?;
+
+        if self.drifts.is_empty() {
+            return write!(f, "\nEverything is in sync.");
+        }
+
+        let mut length = 0;
+        for (idx, (drift, repair)) in self.drifts.iter().enumerate() {
+            let status = match repair {
+                None => "reported",
+                Some(Ok(())) => "repaired",
+                Some(Err(_)) => "repair failed",
+            };
+            let line = format!("\n- {drift}: **{status}**");
+
+            length += line.len();
+            if length > MAX_REPORT_LENGTH {
+                return write!(f, "\n…and {} more", self.drifts.len() - idx);
+            }
+            f.write_str(&line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the `custom_roles` table with the guild and repairs drifts if configured to.
+pub async fn run(state: &AppState) -> Result<Report> {
+    let guild_id = state.config.env.guild_id;
+    let auto_repair = state.config.read().unwrap().reconcile_auto_repair;
+
+    let guild_roles = state
+        .app
+        .roles(guild_id)
+        .await?
+        .model()
+        .await?
+        .into_iter()
+        .map(|role| role.id)
+        .collect::<HashSet<_>>();
+
+    let mut drifts = vec![];
+    for role in state.db.fetch_custom_roles().await? {
+        if !guild_roles.contains(&role.role_id) {
+            drifts.push(Drift::MissingRole(role));
+            continue;
+        }
+
+        let member = match state.app.guild_member(guild_id, role.user_id).await {
+            Ok(response) => response.model().await?,
+            Err(err) => match err.kind() {
+                ErrorType::Response { status, .. } if status.get() == 404 => {
+                    drifts.push(Drift::OwnerLeft(role));
+                    continue;
+                }
+                _ => return Err(err.into()),
+            },
+        };
+
+        if !member.roles.contains(&role.role_id) {
+            drifts.push(Drift::RoleNotAssigned(role));
+        }
+    }
+
+    let mut report = Report {
+        auto_repair,
+        drifts: Vec::with_capacity(drifts.len()),
+    };
+    for drift in drifts {
+        let repair = match auto_repair {
+            true => Some(repair(state, &drift).await),
+            false => None,
+        };
+        if let Some(Err(err)) = &repair {
+            error!(?err, "Unable to repair drift: {drift}");
+        }
+        report.drifts.push((drift, repair));
+    }
+
+    Ok(report)
+}
+
+/// Runs a reconciliation pass and sends its report to the admin log channel.
+pub async fn run_and_report(state: &AppState) -> Result<Report> {
+    let report = run(state).await?;
+    info!(
+        drifts = report.drifts.len(),
+        "Custom role reconciliation done"
+    );
+
+    if let Err(err) = state.admin_log(&report.to_string()).await {
+        error!(?err, "Unable to send the reconciliation report");
+    }
+
+    Ok(report)
+}
+
+async fn repair(state: &AppState, drift: &Drift) -> Result<()> {
+    let guild_id = state.config.env.guild_id;
+
+    match drift {
+        Drift::MissingRole(role) => {
+            state.db.delete_custom_role_by_role_id(role.role_id).await?;
+            state.cache.user_custom_roles.remove(role.user_id);
+        }
+        Drift::OwnerLeft(role) => {
+            // Drop it from the cache first so the RoleDelete event doesn't DM the owner
+            state.cache.user_custom_roles.remove(role.user_id);
+            state.app.delete_role(guild_id, role.role_id).await?;
+            state.db.delete_custom_role_by_role_id(role.role_id).await?;
+        }
+        Drift::RoleNotAssigned(role) => {
+            state
+                .app
+                .add_guild_member_role(guild_id, role.user_id, role.role_id)
+                .await?;
+        }
+    }
+
+    Ok(())
+}